@@ -0,0 +1,11 @@
+extern crate lut_build;
+
+fn main() {
+    lut_build::Builder::new("src/http_chars.toml")
+        .write_to_out_dir("http_chars.rs")
+        .unwrap_or_else(|err| panic!("{}", err));
+
+    lut_build::Builder::new("src/digits.json")
+        .write_to_out_dir("digits.rs")
+        .unwrap_or_else(|err| panic!("{}", err));
+}
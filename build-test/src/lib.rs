@@ -0,0 +1,38 @@
+//! Checks that the code generated by `lut-build` from the descriptions in
+//! this crate compiles and works, see `build.rs`.
+extern crate lut;
+
+pub mod http_chars {
+    include!(concat!(env!("OUT_DIR"), "/http_chars.rs"));
+}
+
+pub mod digits {
+    include!(concat!(env!("OUT_DIR"), "/digits.rs"));
+}
+
+
+#[cfg(test)]
+mod test {
+    use lut::Table;
+    use crate::http_chars::*;
+    use crate::digits::*;
+
+    #[test]
+    fn generated_tables() {
+        assert!(HttpChars::check_all("Content-Type", Token));
+        assert!(!HttpChars::check_all("a b", Token));
+        assert!(HttpChars::check_all("a b\t", TokenOrWs));
+        assert!(HttpChars::check_all(&[0x80u8, 0xFF][..], ObsText));
+
+        assert!(Digits::check_all("0123456789", DigitAndHex));
+        assert!(!Digits::check_at(b'a' as usize, DigitAndHex));
+        assert!(Digits::check_at(b'a' as usize, HexDigit));
+    }
+
+    #[test]
+    fn generated_merge() {
+        assert!(HeaderChars::check_all("text/html; charset=utf-8", Token | Ws | Delimiter));
+        assert!(!HeaderChars::check_at(0x80, Token | Ws | Delimiter));
+        assert_eq!(format!("{:?}", HeaderChars::flags_at(b'"' as usize)), "Delimiter");
+    }
+}
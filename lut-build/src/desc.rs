@@ -0,0 +1,93 @@
+//! the structure of a table description file
+
+/// the content of a description file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Description {
+    #[serde(default, rename = "table")]
+    pub tables: Vec<TableDesc>,
+    #[serde(default, rename = "merge")]
+    pub merges: Vec<MergeDesc>,
+    #[serde(default, rename = "accessor")]
+    pub accessors: Vec<AccessorDesc>,
+}
+
+/// a table generated through `new_table!`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TableDesc {
+    pub name: String,
+    #[serde(default)]
+    pub doc: Option<String>,
+    #[serde(default)]
+    pub visibility: String,
+    /// defaults to `visibility`
+    #[serde(default)]
+    pub flags_visibility: Option<String>,
+    pub cell_type: String,
+    pub size: usize,
+    #[serde(default, rename = "flag")]
+    pub flags: Vec<FlagDesc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FlagDesc {
+    pub name: String,
+    pub short_name: String,
+    #[serde(default)]
+    pub doc: Option<String>,
+    /// every char of the string is part of the set
+    #[serde(default)]
+    pub chars: String,
+    /// inclusive ranges
+    #[serde(default)]
+    pub ranges: Vec<(Bound, Bound)>,
+    #[serde(default)]
+    pub indices: Vec<u32>,
+}
+
+/// a bound of a range, either a string with a single char or an index
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Bound {
+    Char(String),
+    Index(u32),
+}
+
+/// a table generated through `merge_tables!`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MergeDesc {
+    pub name: String,
+    #[serde(default)]
+    pub doc: Option<String>,
+    #[serde(default)]
+    pub visibility: String,
+    pub cell_type: String,
+    pub size: usize,
+    #[serde(rename = "source")]
+    pub sources: Vec<SourceDesc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SourceDesc {
+    pub table: String,
+    pub flags: Vec<String>,
+}
+
+/// an accessor generated through `accessor_any!` or `accessor_all!`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccessorDesc {
+    pub name: String,
+    #[serde(default)]
+    pub doc: Option<String>,
+    #[serde(default)]
+    pub visibility: String,
+    #[serde(default)]
+    pub any: Vec<String>,
+    #[serde(default)]
+    pub all: Vec<String>,
+}
@@ -0,0 +1,79 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// the error returned when generating code from a description fails
+#[derive(Debug)]
+pub enum Error {
+    /// reading the description or writing the generated code failed
+    Io { path: PathBuf, error: io::Error },
+    /// the description is no valid TOML/JSON or does not have the expected structure
+    ///
+    /// The message contains the line and column of the error.
+    Parse { path: Option<PathBuf>, message: String },
+    /// entries of the description are invalid, e.g. refer to unknown flags
+    Invalid { path: Option<PathBuf>, entries: Vec<InvalidEntry> },
+}
+
+/// an invalid entry of a description
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InvalidEntry {
+    /// the location of the entry, e.g. ``table `Chars` > flag `Token` > ranges[1]``
+    pub entry: String,
+    pub message: String,
+}
+
+impl Error {
+
+    /// sets the path of the description the error occurred in
+    pub(crate) fn in_file(mut self, file: PathBuf) -> Self {
+        match self {
+            Error::Parse { ref mut path, .. } | Error::Invalid { ref mut path, .. } => {
+                *path = Some(file);
+            },
+            Error::Io { .. } => ()
+        }
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        let prefix = |path: &Option<PathBuf>| match *path {
+            Some(ref path) => format!("{}: ", path.display()),
+            None => String::new()
+        };
+        match *self {
+            Error::Io { ref path, ref error } =>
+                write!(fter, "{}: {}", path.display(), error),
+            Error::Parse { ref path, ref message } =>
+                write!(fter, "{}{}", prefix(path), message.trim_end()),
+            Error::Invalid { ref path, ref entries } => {
+                let prefix = prefix(path);
+                for (idx, entry) in entries.iter().enumerate() {
+                    if idx > 0 {
+                        writeln!(fter)?;
+                    }
+                    write!(fter, "{}{}", prefix, entry)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for InvalidEntry {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        write!(fter, "{}: {}", self.entry, self.message)
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io { ref error, .. } => Some(error),
+            _ => None
+        }
+    }
+}
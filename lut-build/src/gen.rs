@@ -0,0 +1,548 @@
+//! validation of a description and generation of the macro invocations
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::desc::{Description, TableDesc, FlagDesc, Bound, MergeDesc, AccessorDesc};
+use crate::error::InvalidEntry;
+
+const KEYWORDS: &[&str] = &[
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "async", "await", "dyn", "abstract", "become", "box", "do", "final",
+    "macro", "override", "priv", "typeof", "unsized", "virtual", "yield", "try", "_"
+];
+
+/// what is known about a (merged) table when validating later entries
+struct TableInfo {
+    flags: Vec<String>,
+    /// all flags including the not merged ones of merged tables
+    flag_count: usize,
+    size: usize,
+}
+
+/// validates the description and generates the code for it
+pub fn generate(desc: &Description, source_name: &str) -> Result<String, Vec<InvalidEntry>> {
+    let mut gen = Generator {
+        code: String::new(),
+        errors: Vec::new(),
+        names: HashMap::new(),
+        tables: HashMap::new(),
+    };
+    let _ = writeln!(gen.code, "// generated by lut-build from `{}`, do not edit", source_name);
+    for (idx, table) in desc.tables.iter().enumerate() {
+        gen.table(idx, table);
+    }
+    for (idx, merge) in desc.merges.iter().enumerate() {
+        gen.merge(idx, merge);
+    }
+    for (idx, accessor) in desc.accessors.iter().enumerate() {
+        gen.accessor(idx, accessor);
+    }
+
+    if gen.errors.is_empty() {
+        Ok(gen.code)
+    } else {
+        Err(gen.errors)
+    }
+}
+
+struct Generator {
+    code: String,
+    errors: Vec<InvalidEntry>,
+    /// all defined types, as tables, flags and accessors share one namespace
+    names: HashMap<String, String>,
+    tables: HashMap<String, TableInfo>,
+}
+
+impl Generator {
+
+    fn error(&mut self, entry: &str, message: String) {
+        self.errors.push(InvalidEntry { entry: entry.to_owned(), message });
+    }
+
+    /// checks that `name` is a valid and not yet used type name
+    fn define(&mut self, entry: &str, name: &str) {
+        if !is_ident(name) {
+            self.error(entry, format!("`{}` is not a valid identifier", name));
+        } else if let Some(first) = self.names.get(name).cloned() {
+            self.error(entry, format!("`{}` is already defined by {}", name, first));
+        } else {
+            self.names.insert(name.to_owned(), entry.to_owned());
+        }
+    }
+
+    fn visibility(&mut self, entry: &str, vis: &str) {
+        if !is_visibility(vis) {
+            self.error(entry, format!(
+                "invalid visibility `{}`, expected `pub`, `pub(...)` or an empty string", vis));
+        }
+    }
+
+    /// checks the cell type and returns the type to use in the generated code
+    fn cell_type(&mut self, entry: &str, cell_type: &str, flag_count: usize) -> String {
+        match max_flag_count(cell_type) {
+            None => {
+                self.error(&format!("{} > cell_type", entry), format!(
+                    "unsupported cell type `{}`, expected one of u8, u16, u32, u64, u128, \
+                     usize or BitSet<N>", cell_type));
+            },
+            Some(Some(max)) if flag_count > max => {
+                self.error(&format!("{} > cell_type", entry), format!(
+                    "cell type `{}` can hold at most {} flags but {} are needed",
+                    cell_type, max, flag_count));
+            },
+            Some(_) => ()
+        }
+        if cell_type.starts_with("BitSet") {
+            format!("::lut::{}", cell_type)
+        } else {
+            cell_type.to_owned()
+        }
+    }
+
+    fn table(&mut self, idx: usize, table: &TableDesc) {
+        let entry = entry_name("table", idx, &table.name);
+        self.define(&entry, &table.name);
+        self.visibility(&entry, &table.visibility);
+        if let Some(ref flags_vis) = table.flags_visibility {
+            self.visibility(&entry, flags_vis);
+        }
+        let flags_vis = table.flags_visibility.as_ref().unwrap_or(&table.visibility);
+        let cell_type = self.cell_type(&entry, &table.cell_type, table.flags.len());
+        if table.size == 0 {
+            self.error(&format!("{} > size", entry), "the size has to be at least 1".to_owned());
+        }
+
+        let mut short_names = HashSet::new();
+        let mut flags = String::new();
+        let mut sets = String::new();
+        for (fidx, flag) in table.flags.iter().enumerate() {
+            let flag_entry = format!("{} > {}", entry, entry_name("flag", fidx, &flag.name));
+            self.define(&flag_entry, &flag.name);
+            if !is_ident(&flag.short_name) {
+                self.error(&flag_entry, format!(
+                    "short name `{}` is not a valid identifier", flag.short_name));
+            } else if !short_names.insert(flag.short_name.clone()) {
+                self.error(&flag_entry, format!(
+                    "short name `{}` is used by more than one flag", flag.short_name));
+            }
+            let set = self.set(&flag_entry, flag, table.size);
+
+            let sep = if fidx == 0 { "" } else { "," };
+            let _ = write!(flags, "{}\n{}", sep, doc(&flag.doc, "        "));
+            let _ = write!(flags, "        {} = {}", flag.name, flag.short_name);
+            let _ = write!(sets, "{}\n            {} = [{}]", sep, flag.short_name, set.join(", "));
+        }
+
+        self.tables.insert(table.name.clone(), TableInfo {
+            flags: table.flags.iter().map(|flag| flag.name.clone()).collect(),
+            flag_count: table.flags.len(),
+            size: table.size,
+        });
+
+        let _ = write!(self.code, "\n::lut::new_table! {{\n    {}flags {{{}\n    }}\n",
+            vis(flags_vis), flags);
+        let _ = writeln!(self.code, "{}    {}struct {} {{", doc(&table.doc, "    "),
+            vis(&table.visibility), table.name);
+        let _ = write!(self.code, "        static data: [{}; {}] = {{{}\n        }};\n    }}\n}}\n",
+            cell_type, table.size, sets);
+    }
+
+    /// validates the set of a flag and returns its items as rust code
+    fn set(&mut self, entry: &str, flag: &FlagDesc, size: usize) -> Vec<String> {
+        let mut items = Vec::new();
+        for chr in flag.chars.chars() {
+            if chr as usize >= size {
+                self.error(&format!("{} > chars", entry), format!(
+                    "{:?} is outside of the table with a size of {}", chr, size));
+            }
+            items.push(format!("{:?}", chr));
+        }
+        for (ridx, (start, end)) in flag.ranges.iter().enumerate() {
+            let range_entry = format!("{} > ranges[{}]", entry, ridx);
+            let bounds = (self.bound(&range_entry, start), self.bound(&range_entry, end));
+            if let (Some((start, start_code)), Some((end, end_code))) = bounds {
+                if start > end {
+                    self.error(&range_entry, format!(
+                        "the range starts at {} after its end {}", start_code, end_code));
+                } else if end as usize >= size {
+                    self.error(&range_entry, format!(
+                        "{} is outside of the table with a size of {}", end_code, size));
+                }
+                items.push(format!("{}..={}", start_code, end_code));
+            }
+        }
+        for (iidx, &index) in flag.indices.iter().enumerate() {
+            if index as usize >= size {
+                self.error(&format!("{} > indices[{}]", entry, iidx), format!(
+                    "{} is outside of the table with a size of {}", index, size));
+            }
+            items.push(format!("0x{:02X}", index));
+        }
+        items
+    }
+
+    /// returns the index of the bound and its rust code
+    fn bound(&mut self, entry: &str, bound: &Bound) -> Option<(u32, String)> {
+        match *bound {
+            Bound::Index(index) => Some((index, format!("0x{:02X}", index))),
+            Bound::Char(ref string) => {
+                let mut chars = string.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(chr), None) => Some((chr as u32, format!("{:?}", chr))),
+                    _ => {
+                        self.error(entry, format!(
+                            "bound {:?} has to be a single char or an index", string));
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    fn merge(&mut self, idx: usize, merge: &MergeDesc) {
+        let entry = entry_name("merge", idx, &merge.name);
+        self.define(&entry, &merge.name);
+        self.visibility(&entry, &merge.visibility);
+        if merge.sources.is_empty() {
+            self.error(&entry, "at least one source table is needed".to_owned());
+        }
+
+        let mut merged = HashSet::new();
+        let mut flag_count = 0;
+        let mut sources = Vec::new();
+        for (sidx, source) in merge.sources.iter().enumerate() {
+            let source_entry = format!("{} > source[{}]", entry, sidx);
+            let (known, source_flag_count, size) = match self.tables.get(&source.table) {
+                Some(info) => (info.flags.clone(), info.flag_count, info.size),
+                None => {
+                    let known = self.tables.keys().cloned().collect::<Vec<_>>();
+                    self.error(&source_entry, format!(
+                        "unknown table `{}`, expected one of: {}", source.table, list(known)));
+                    continue;
+                }
+            };
+            flag_count += source_flag_count;
+            if merge.size > size {
+                self.error(&source_entry, format!(
+                    "the size {} is larger than the size {} of table `{}`",
+                    merge.size, size, source.table));
+            }
+            for (fidx, flag) in source.flags.iter().enumerate() {
+                let flag_entry = format!("{} > flags[{}]", source_entry, fidx);
+                if !known.contains(flag) {
+                    self.error(&flag_entry, format!(
+                        "unknown flag `{}` of table `{}`, expected one of: {}",
+                        flag, source.table, list(known.clone())));
+                } else if !merged.insert(flag.clone()) {
+                    self.error(&flag_entry, format!("flag `{}` is merged more than once", flag));
+                }
+            }
+            sources.push(format!("{} {{ {} }}", source.table, source.flags.join(", ")));
+        }
+        let cell_type = self.cell_type(&entry, &merge.cell_type, flag_count);
+        if merge.size == 0 {
+            self.error(&format!("{} > size", entry), "the size has to be at least 1".to_owned());
+        }
+
+        self.tables.insert(merge.name.clone(), TableInfo {
+            flags: merged.into_iter().collect(),
+            flag_count,
+            size: merge.size,
+        });
+
+        let _ = write!(self.code, "\n::lut::merge_tables! {{\n{}    {}struct {} {{\n",
+            doc(&merge.doc, "    "), vis(&merge.visibility), merge.name);
+        let _ = write!(self.code, "        static data: [{}; {}]\n            = {};\n    }}\n}}\n",
+            cell_type, merge.size, sources.join("\n            + "));
+    }
+
+    fn accessor(&mut self, idx: usize, accessor: &AccessorDesc) {
+        let entry = entry_name("accessor", idx, &accessor.name);
+        self.define(&entry, &accessor.name);
+        self.visibility(&entry, &accessor.visibility);
+        let (kind, flags, sep) = match (accessor.any.is_empty(), accessor.all.is_empty()) {
+            (false, true) => ("any", &accessor.any, " | "),
+            (true, false) => ("all", &accessor.all, " & "),
+            _ => {
+                self.error(&entry, "exactly one of `any` and `all` has to list flags".to_owned());
+                return;
+            }
+        };
+
+        for (fidx, flag) in flags.iter().enumerate() {
+            let known = self.tables.values().any(|table| table.flags.contains(flag));
+            if !known {
+                self.error(&format!("{} > {}[{}]", entry, kind, fidx),
+                    format!("unknown flag `{}`", flag));
+            }
+        }
+
+        let _ = write!(self.code, "\n::lut::accessor_{}! {{\n{}    {}{} = {}\n}}\n",
+            kind, doc(&accessor.doc, "    "), vis(&accessor.visibility), accessor.name,
+            flags.join(sep));
+    }
+}
+
+/// names an entry by its name or, if it has none, by its position
+fn entry_name(kind: &str, idx: usize, name: &str) -> String {
+    if name.is_empty() {
+        format!("{}[{}]", kind, idx)
+    } else {
+        format!("{} `{}`", kind, name)
+    }
+}
+
+/// the doc comment as attribute lines, with `indent` before every line
+fn doc(doc: &Option<String>, indent: &str) -> String {
+    match *doc {
+        None => String::new(),
+        Some(ref doc) => doc.lines()
+            .map(|line| format!("{}#[doc = {:?}]\n", indent, format!(" {}", line)))
+            .collect()
+    }
+}
+
+/// the visibility followed by a space, if there is one
+fn vis(vis: &str) -> String {
+    if vis.is_empty() { String::new() } else { format!("{} ", vis) }
+}
+
+fn list(mut names: Vec<String>) -> String {
+    if names.is_empty() {
+        return "-".to_owned();
+    }
+    names.sort();
+    names.iter().map(|name| format!("`{}`", name)).collect::<Vec<_>>().join(", ")
+}
+
+fn is_ident(name: &str) -> bool {
+    let mut chars = name.chars();
+    let starts_valid = match chars.next() {
+        Some(first) => first == '_' || first.is_ascii_alphabetic(),
+        None => false
+    };
+    starts_valid
+        && chars.all(|chr| chr == '_' || chr.is_ascii_alphanumeric())
+        && !KEYWORDS.contains(&name)
+}
+
+fn is_visibility(vis: &str) -> bool {
+    vis.is_empty() || vis == "pub" || (vis.starts_with("pub(") && vis.ends_with(')'))
+}
+
+/// returns the max flag count, `Some(None)` if it depends on the target
+/// and `None` for unsupported types
+pub fn max_flag_count(cell_type: &str) -> Option<Option<usize>> {
+    match cell_type {
+        "u8" => Some(Some(8)),
+        "u16" => Some(Some(16)),
+        "u32" => Some(Some(32)),
+        "u64" => Some(Some(64)),
+        "u128" => Some(Some(128)),
+        "usize" => Some(None),
+        _ => {
+            let words = cell_type.strip_prefix("BitSet<")?.strip_suffix('>')?;
+            words.trim().parse::<usize>().ok().map(|words| Some(words * 64))
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_toml;
+
+    fn errors(source: &str) -> Vec<String> {
+        let desc = parse_toml(source).unwrap();
+        match generate(&desc, "test.toml") {
+            Ok(code) => panic!("expected generation to fail, got:\n{}", code),
+            Err(errors) => errors.iter().map(|error| error.to_string()).collect()
+        }
+    }
+
+    #[test]
+    fn generates_macro_invocations() {
+        let desc = parse_toml(r#"
+            [[table]]
+            name = "Chars"
+            visibility = "pub"
+            doc = "some chars"
+            cell_type = "u8"
+            size = 128
+
+            [[table.flag]]
+            name = "Token"
+            short_name = "TO"
+            doc = "token chars"
+            chars = "!'"
+            ranges = [["0", "9"], [0x41, 0x5A]]
+
+            [[table.flag]]
+            name = "Ws"
+            short_name = "WS"
+            indices = [9, 32]
+
+            [[table]]
+            name = "Other"
+            cell_type = "BitSet<1>"
+            size = 128
+
+            [[table.flag]]
+            name = "Digit"
+            short_name = "DI"
+            ranges = [["0", "9"]]
+
+            [[merge]]
+            name = "Merged"
+            cell_type = "u16"
+            size = 128
+            source = [ { table = "Chars", flags = ["Token", "Ws"] }, { table = "Other", flags = ["Digit"] } ]
+
+            [[accessor]]
+            name = "TokenOrWs"
+            visibility = "pub(crate)"
+            any = ["Token", "Ws"]
+        "#).unwrap();
+        assert_eq!(generate(&desc, "chars.toml").unwrap(), r#"// generated by lut-build from `chars.toml`, do not edit
+
+::lut::new_table! {
+    pub flags {
+        #[doc = " token chars"]
+        Token = TO,
+        Ws = WS
+    }
+    #[doc = " some chars"]
+    pub struct Chars {
+        static data: [u8; 128] = {
+            TO = ['!', '\'', '0'..='9', 0x41..=0x5A],
+            WS = [0x09, 0x20]
+        };
+    }
+}
+
+::lut::new_table! {
+    flags {
+        Digit = DI
+    }
+    struct Other {
+        static data: [::lut::BitSet<1>; 128] = {
+            DI = ['0'..='9']
+        };
+    }
+}
+
+::lut::merge_tables! {
+    struct Merged {
+        static data: [u16; 128]
+            = Chars { Token, Ws }
+            + Other { Digit };
+    }
+}
+
+::lut::accessor_any! {
+    pub(crate) TokenOrWs = Token | Ws
+}
+"#);
+    }
+
+    #[test]
+    fn invalid_flags() {
+        assert_eq!(errors(r#"
+            [[table]]
+            name = "Chars"
+            cell_type = "u8"
+            size = 128
+
+            [[table.flag]]
+            name = "Token"
+            short_name = "TO"
+            chars = "é"
+            ranges = [["9", "0"], ["ab", "c"], ["a", 0x80]]
+
+            [[table.flag]]
+            name = "Token"
+            short_name = "TO"
+            indices = [128]
+        "#), vec![
+            "table `Chars` > flag `Token` > chars: 'é' is outside of the table with a size of 128",
+            "table `Chars` > flag `Token` > ranges[0]: the range starts at '9' after its end '0'",
+            "table `Chars` > flag `Token` > ranges[1]: bound \"ab\" has to be a single char or an index",
+            "table `Chars` > flag `Token` > ranges[2]: 0x80 is outside of the table with a size of 128",
+            "table `Chars` > flag `Token`: `Token` is already defined by table `Chars` > flag `Token`",
+            "table `Chars` > flag `Token`: short name `TO` is used by more than one flag",
+            "table `Chars` > flag `Token` > indices[0]: 128 is outside of the table with a size of 128",
+        ]);
+    }
+
+    #[test]
+    fn invalid_tables() {
+        assert_eq!(errors(r#"
+            [[table]]
+            name = "struct"
+            visibility = "public"
+            cell_type = "u2"
+            size = 0
+
+            [[table]]
+            name = "Small"
+            cell_type = "u8"
+            size = 4
+            flag = [
+                { name = "F1", short_name = "F1" }, { name = "F2", short_name = "F2" },
+                { name = "F3", short_name = "F3" }, { name = "F4", short_name = "F4" },
+                { name = "F5", short_name = "F5" }, { name = "F6", short_name = "F6" },
+                { name = "F7", short_name = "F7" }, { name = "F8", short_name = "F8" },
+                { name = "F9", short_name = "9" },
+            ]
+        "#), vec![
+            "table `struct`: `struct` is not a valid identifier",
+            "table `struct`: invalid visibility `public`, expected `pub`, `pub(...)` or an empty string",
+            "table `struct` > cell_type: unsupported cell type `u2`, expected one of u8, u16, u32, \
+             u64, u128, usize or BitSet<N>",
+            "table `struct` > size: the size has to be at least 1",
+            "table `Small` > cell_type: cell type `u8` can hold at most 8 flags but 9 are needed",
+            "table `Small` > flag `F9`: short name `9` is not a valid identifier",
+        ]);
+    }
+
+    #[test]
+    fn invalid_merges_and_accessors() {
+        assert_eq!(errors(r#"
+            [[table]]
+            name = "Chars"
+            cell_type = "u8"
+            size = 4
+            flag = [ { name = "Token", short_name = "TO" }, { name = "Ws", short_name = "WS" } ]
+
+            [[merge]]
+            name = "Merged"
+            cell_type = "u8"
+            size = 8
+            source = [
+                { table = "Chars", flags = ["Token", "Digit"] },
+                { table = "Chars", flags = ["Token"] },
+                { table = "Missing", flags = [] },
+            ]
+
+            [[accessor]]
+            name = "TokenOrDigit"
+            any = ["Token", "Digit"]
+
+            [[accessor]]
+            name = "Neither"
+        "#), vec![
+            "merge `Merged` > source[0]: the size 8 is larger than the size 4 of table `Chars`",
+            "merge `Merged` > source[0] > flags[1]: unknown flag `Digit` of table `Chars`, \
+             expected one of: `Token`, `Ws`",
+            "merge `Merged` > source[1]: the size 8 is larger than the size 4 of table `Chars`",
+            "merge `Merged` > source[1] > flags[0]: flag `Token` is merged more than once",
+            "merge `Merged` > source[2]: unknown table `Missing`, expected one of: `Chars`",
+            "accessor `TokenOrDigit` > any[1]: unknown flag `Digit`",
+            "accessor `Neither`: exactly one of `any` and `all` has to list flags",
+        ]);
+    }
+}
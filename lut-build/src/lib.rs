@@ -0,0 +1,295 @@
+//! generates `lut` tables from TOML or JSON descriptions in build scripts
+//!
+//! The description lists tables with the character sets of their flags,
+//! merged tables and accessors. From it the same `new_table!`,
+//! `merge_tables!` and `accessor_any!`/`accessor_all!` invocations are
+//! generated which would be written by hand, so the generated code only
+//! needs `lut` as dependency. Invalid entries are reported with their
+//! location in the description, e.g.:
+//!
+//! ```text
+//! chars.toml: table `Chars` > flag `Token` > ranges[1]: the range starts at '9' after its end '0'
+//! ```
+//!
+//! # Description
+//!
+//! ```toml
+//! [[table]]
+//! name = "Chars"
+//! visibility = "pub"          # optional, defaults to private
+//! flags_visibility = "pub"    # optional, defaults to `visibility`
+//! doc = "chars of http headers" # optional
+//! cell_type = "u8"            # u8 to u128, usize or BitSet<N>
+//! size = 128
+//!
+//! [[table.flag]]
+//! name = "Token"
+//! short_name = "TO"
+//! chars = "!#$%&'*+-.^_`|~"   # every char is in the set
+//! ranges = [["0", "9"], ["A", "Z"], ["a", "z"]]  # inclusive, chars or indices
+//!
+//! [[table.flag]]
+//! name = "Ws"
+//! short_name = "WS"
+//! indices = [9, 32]
+//!
+//! [[merge]]
+//! name = "Merged"
+//! cell_type = "u16"
+//! size = 128
+//! source = [ { table = "Chars", flags = ["Token", "Ws"] } ]
+//!
+//! [[accessor]]
+//! name = "TokenOrWs"
+//! any = ["Token", "Ws"]       # or `all = [...]`
+//! ```
+//!
+//! The same structure can be written as JSON, e.g. `{ "table": [ ... ] }`.
+//!
+//! # Example
+//!
+//! In `build.rs`:
+//!
+//! ```no_run
+//! extern crate lut_build;
+//!
+//! fn main() {
+//!     lut_build::Builder::new("src/chars.toml")
+//!         .write_to_out_dir("chars.rs")
+//!         .unwrap_or_else(|err| panic!("{}", err));
+//! }
+//! ```
+//!
+//! And in the crate using `lut` as dependency:
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/chars.rs"));
+//! ```
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate toml;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+mod desc;
+mod error;
+mod gen;
+mod spec;
+
+pub use error::{Error, InvalidEntry};
+pub use spec::{TableSpec, FlagSpec};
+
+/// the format of a description
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Format {
+    Toml,
+    Json,
+}
+
+impl Format {
+
+    /// returns the format matching the extension of `path`
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Some(Format::Toml),
+            Some("json") => Some(Format::Json),
+            _ => None
+        }
+    }
+}
+
+/// generates code from a description file, see the crate documentation
+#[derive(Clone, Debug)]
+pub struct Builder {
+    input: PathBuf,
+    format: Option<Format>,
+}
+
+impl Builder {
+
+    /// creates a builder reading the description from `input`
+    ///
+    /// The format is detected by the file extension, `.toml` or `.json`.
+    pub fn new<P: AsRef<Path>>(input: P) -> Self {
+        Builder { input: input.as_ref().to_owned(), format: None }
+    }
+
+    /// sets the format instead of detecting it by the file extension
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// reads the description and returns the generated code
+    pub fn generate(&self) -> Result<String, Error> {
+        let (source, format) = self.read()?;
+        let name = self.input.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        generate_code(&source, format, &name).map_err(|err| err.in_file(self.input.clone()))
+    }
+
+    /// reads and validates the description and returns its tables
+    pub fn tables(&self) -> Result<Vec<TableSpec>, Error> {
+        let (source, format) = self.read()?;
+        load_tables(&source, format).map_err(|err| err.in_file(self.input.clone()))
+    }
+
+    fn read(&self) -> Result<(String, Format), Error> {
+        let source = fs::read_to_string(&self.input)
+            .map_err(|error| Error::Io { path: self.input.clone(), error })?;
+        match self.format.or_else(|| Format::from_path(&self.input)) {
+            Some(format) => Ok((source, format)),
+            None => Err(Error::Parse {
+                path: Some(self.input.clone()),
+                message: "unknown format, expected a `.toml` or `.json` file".to_owned()
+            })
+        }
+    }
+
+    /// writes the generated code to `output`
+    ///
+    /// This also tells cargo to rerun the build script if the description changes.
+    pub fn write_to<P: AsRef<Path>>(&self, output: P) -> Result<(), Error> {
+        println!("cargo:rerun-if-changed={}", self.input.display());
+        let code = self.generate()?;
+        let output = output.as_ref();
+        fs::write(output, code).map_err(|error| Error::Io { path: output.to_owned(), error })
+    }
+
+    /// writes the generated code to `file_name` in the `OUT_DIR` of the build script
+    ///
+    /// Returns the path of the written file.
+    pub fn write_to_out_dir(&self, file_name: &str) -> Result<PathBuf, Error> {
+        let out_dir = env::var_os("OUT_DIR").ok_or_else(|| Error::Io {
+            path: PathBuf::from(file_name),
+            error: ::std::io::Error::new(::std::io::ErrorKind::NotFound,
+                "OUT_DIR is not set, this has to be called from a build script")
+        })?;
+        let output = Path::new(&out_dir).join(file_name);
+        self.write_to(&output)?;
+        Ok(output)
+    }
+}
+
+/// generates code from the description `source`
+///
+/// `source_name` is mentioned in the header comment of the generated code.
+pub fn generate_code(source: &str, format: Format, source_name: &str) -> Result<String, Error> {
+    let desc = parse(source, format)?;
+    gen::generate(&desc, source_name)
+        .map_err(|entries| Error::Invalid { path: None, entries })
+}
+
+/// validates the description `source` and returns its tables
+///
+/// The tables are listed in the order of the description, merged tables
+/// after all other tables.
+pub fn load_tables(source: &str, format: Format) -> Result<Vec<TableSpec>, Error> {
+    let desc = parse(source, format)?;
+    gen::generate(&desc, "")
+        .map_err(|entries| Error::Invalid { path: None, entries })?;
+    Ok(spec::evaluate(&desc))
+}
+
+fn parse(source: &str, format: Format) -> Result<desc::Description, Error> {
+    match format {
+        Format::Toml => parse_toml(source),
+        Format::Json => serde_json::from_str(source)
+            .map_err(|err| Error::Parse { path: None, message: err.to_string() }),
+    }
+}
+
+fn parse_toml(source: &str) -> Result<desc::Description, Error> {
+    toml::from_str(source).map_err(|err| Error::Parse { path: None, message: err.to_string() })
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn json_and_toml_generate_the_same_code() {
+        let toml = r#"
+            [[table]]
+            name = "Chars"
+            cell_type = "u8"
+            size = 128
+            flag = [ { name = "Digit", short_name = "DI", ranges = [["0", "9"]] } ]
+        "#;
+        let json = r#"{
+            "table": [{
+                "name": "Chars",
+                "cell_type": "u8",
+                "size": 128,
+                "flag": [ { "name": "Digit", "short_name": "DI", "ranges": [["0", "9"]] } ]
+            }]
+        }"#;
+        assert_eq!(generate_code(toml, Format::Toml, "chars").unwrap(),
+            generate_code(json, Format::Json, "chars").unwrap());
+    }
+
+    #[test]
+    fn tables() {
+        let tables = load_tables(r#"
+            [[table]]
+            name = "Chars"
+            cell_type = "u8"
+            size = 8
+            flag = [
+                { name = "Low", short_name = "LO", ranges = [[0, 3]], indices = [2, 7] },
+                { name = "Odd", short_name = "OD", indices = [1, 3, 5, 7] },
+            ]
+
+            [[merge]]
+            name = "Merged"
+            cell_type = "usize"
+            size = 4
+            source = [ { table = "Chars", flags = ["Odd"] } ]
+        "#, Format::Toml).unwrap();
+
+        let flag = |name: &str, short_name: &str, indices: Vec<usize>| FlagSpec {
+            name: name.to_owned(), short_name: short_name.to_owned(), indices
+        };
+        assert_eq!(tables, vec![
+            TableSpec {
+                name: "Chars".to_owned(), cell_type: "u8".to_owned(), size: 8, flag_count: 2,
+                max_flag_count: Some(8), merged: false,
+                flags: vec![flag("Low", "LO", vec![0, 1, 2, 3, 7]), flag("Odd", "OD", vec![1, 3, 5, 7])]
+            },
+            TableSpec {
+                name: "Merged".to_owned(), cell_type: "usize".to_owned(), size: 4, flag_count: 2,
+                max_flag_count: None, merged: true,
+                flags: vec![flag("Odd", "OD", vec![1, 3])]
+            },
+        ]);
+    }
+
+    #[test]
+    fn parse_errors_have_a_location() {
+        let err = generate_code("[[table]]\nname = 12", Format::Toml, "chars").unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{}", err);
+
+        let err = generate_code("{ \"table\": [{ \"nam\": \"x\" }] }", Format::Json, "chars")
+            .unwrap_err();
+        assert!(err.to_string().starts_with("unknown field `nam`"), "{}", err);
+        assert!(err.to_string().contains("line 1 column"), "{}", err);
+    }
+
+    #[test]
+    fn errors_name_the_file() {
+        let err = generate_code("[[accessor]]\nname = \"A\"", Format::Toml, "chars")
+            .unwrap_err()
+            .in_file(PathBuf::from("chars.toml"));
+        assert_eq!(err.to_string(),
+            "chars.toml: accessor `A`: exactly one of `any` and `all` has to list flags");
+
+        let err = Builder::new("chars.txt").generate().unwrap_err();
+        assert!(err.to_string().starts_with("chars.txt: "), "{}", err);
+    }
+}
@@ -0,0 +1,107 @@
+//! evaluation of a validated description into the cells of its tables
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::desc::{Description, FlagDesc, Bound};
+use crate::gen::max_flag_count;
+
+/// a table of a description with the indices at which its flags are set
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TableSpec {
+    pub name: String,
+    pub cell_type: String,
+    pub size: usize,
+    /// the number of bits used by flags
+    ///
+    /// For merged tables this includes the bits of flags which are not merged.
+    pub flag_count: usize,
+    /// the number of flags the cell type can hold, `None` for `usize`
+    pub max_flag_count: Option<usize>,
+    /// true if the table is created by merging other tables
+    pub merged: bool,
+    pub flags: Vec<FlagSpec>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FlagSpec {
+    pub name: String,
+    pub short_name: String,
+    /// the ascending indices of all cells the flag is set in
+    pub indices: Vec<usize>,
+}
+
+impl TableSpec {
+
+    pub fn flag(&self, name: &str) -> Option<&FlagSpec> {
+        self.flags.iter().find(|flag| flag.name == name)
+    }
+}
+
+/// evaluates a description, which has to be validated by `gen::generate`
+pub fn evaluate(desc: &Description) -> Vec<TableSpec> {
+    let mut tables = Vec::new();
+    for table in &desc.tables {
+        tables.push(TableSpec {
+            name: table.name.clone(),
+            cell_type: table.cell_type.clone(),
+            size: table.size,
+            flag_count: table.flags.len(),
+            max_flag_count: max_flag_count(&table.cell_type).and_then(|max| max),
+            merged: false,
+            flags: table.flags.iter().map(|flag| FlagSpec {
+                name: flag.name.clone(),
+                short_name: flag.short_name.clone(),
+                indices: indices(flag, table.size),
+            }).collect(),
+        });
+    }
+
+    for merge in &desc.merges {
+        let (flag_count, flags) = {
+            let by_name = tables.iter()
+                .map(|table| (&*table.name, table))
+                .collect::<HashMap<_, _>>();
+            let mut flag_count = 0;
+            let mut flags = Vec::new();
+            for source in &merge.sources {
+                let table = by_name[&*source.table];
+                flag_count += table.flag_count;
+                flags.extend(source.flags.iter().map(|name| {
+                    let flag = table.flag(name).expect("validated description");
+                    FlagSpec {
+                        indices: flag.indices.iter().cloned().filter(|&idx| idx < merge.size).collect(),
+                        ..flag.clone()
+                    }
+                }));
+            }
+            (flag_count, flags)
+        };
+        tables.push(TableSpec {
+            name: merge.name.clone(),
+            cell_type: merge.cell_type.clone(),
+            size: merge.size,
+            flag_count,
+            max_flag_count: max_flag_count(&merge.cell_type).and_then(|max| max),
+            merged: true,
+            flags,
+        });
+    }
+    tables
+}
+
+fn indices(flag: &FlagDesc, size: usize) -> Vec<usize> {
+    let mut indices = BTreeSet::new();
+    indices.extend(flag.chars.chars().map(|chr| chr as usize));
+    for (start, end) in &flag.ranges {
+        indices.extend(bound(start)..=bound(end));
+    }
+    indices.extend(flag.indices.iter().map(|&idx| idx as usize));
+    indices.into_iter().filter(|&idx| idx < size).collect()
+}
+
+fn bound(bound: &Bound) -> usize {
+    match *bound {
+        Bound::Index(index) => index as usize,
+        Bound::Char(ref string) => string.chars().next().expect("validated description") as usize
+    }
+}
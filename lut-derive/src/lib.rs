@@ -0,0 +1,71 @@
+//! procedural macro implementation of `lut`'s `new_table!` and `merge_tables!`
+//!
+//! This crate should not be used directly, instead enable the `derive` feature
+//! of `lut` and use `lut::derive::{new_table, merge_tables}`.
+//!
+//! The macros accept the same syntax as their `macro_rules!` counterparts but
+//! allow any visibility and report errors at the offending cell or flag. After
+//! validating the input they expand to the same (hidden) macros the
+//! `macro_rules!` versions expand to, so the generated code is identical.
+//!
+//! The generated code refers to `lut` as `::lut`. If the dependency is
+//! renamed, or the macros are used inside of `lut`, the path of the crate
+//! can be given as first item of the input, e.g. `crate = ::my_lut;`.
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+#[macro_use]
+extern crate syn;
+
+use proc_macro::TokenStream;
+use syn::{Error, Path, Result};
+use syn::parse::ParseStream;
+
+mod table;
+mod merge;
+
+/// creates a new lookup table, see `lut::new_table!`
+#[proc_macro]
+pub fn new_table(input: TokenStream) -> TokenStream {
+    let def = parse_macro_input!(input as table::TableDef);
+    def.expand()
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// merges multiple lookup tables into one, see `lut::merge_tables!`
+#[proc_macro]
+pub fn merge_tables(input: TokenStream) -> TokenStream {
+    let def = parse_macro_input!(input as merge::MergeDef);
+    def.expand()
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// turns all collected errors into one error, so that all of them are reported
+fn combine_errors(errors: Vec<Error>) -> Result<()> {
+    let mut errors = errors.into_iter();
+    match errors.next() {
+        None => Ok(()),
+        Some(mut first) => {
+            for error in errors {
+                first.combine(error);
+            }
+            Err(first)
+        }
+    }
+}
+
+/// parses the optional `crate = path;` prefix, defaulting to `::lut`
+fn parse_crate_path(input: ParseStream) -> Result<Path> {
+    if input.peek(Token![crate]) && input.peek2(Token![=]) {
+        input.parse::<Token![crate]>()?;
+        input.parse::<Token![=]>()?;
+        let path = input.call(Path::parse_mod_style)?;
+        input.parse::<Token![;]>()?;
+        Ok(path)
+    } else {
+        Ok(parse_quote!(::lut))
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashSet;
+
+use proc_macro2::TokenStream;
+use syn::{Attribute, Error, Expr, Ident, Path, Result, Type, Visibility};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+
+use crate::{combine_errors, parse_crate_path};
+
+/// the input of `merge_tables!`
+pub struct MergeDef {
+    krate: Path,
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    name: Ident,
+    cell_type: Type,
+    size: Expr,
+    sources: Vec<Source>,
+}
+
+struct Source {
+    table: Path,
+    flags: Vec<Path>,
+}
+
+impl Parse for MergeDef {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let krate = parse_crate_path(input)?;
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<Token![struct]>()?;
+        let name = input.parse()?;
+
+        let body;
+        braced!(body in input);
+        body.parse::<Token![static]>()?;
+        body.parse::<Ident>()?;
+        body.parse::<Token![:]>()?;
+        let array_type;
+        bracketed!(array_type in body);
+        let cell_type = array_type.parse()?;
+        array_type.parse::<Token![;]>()?;
+        let size = array_type.parse()?;
+        body.parse::<Token![=]>()?;
+        let sources = Punctuated::<Source, Token![+]>::parse_separated_nonempty(&body)?;
+        body.parse::<Token![;]>()?;
+
+        Ok(MergeDef {
+            krate, attrs, vis, name, cell_type, size,
+            sources: sources.into_iter().collect(),
+        })
+    }
+}
+
+impl Parse for Source {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let table = input.call(Path::parse_mod_style)?;
+        let flags_content;
+        braced!(flags_content in input);
+        let flags = flags_content.parse_terminated(Path::parse_mod_style, Token![,])?;
+        Ok(Source { table, flags: flags.into_iter().collect() })
+    }
+}
+
+impl MergeDef {
+
+    pub fn expand(&self) -> Result<TokenStream> {
+        self.validate()?;
+
+        let MergeDef { ref krate, ref attrs, ref vis, ref name, ref cell_type, .. } = *self;
+        let sources = self.sources.iter().map(|source| {
+            let Source { ref table, ref flags } = *source;
+            quote!( #table [#(#flags),*] )
+        });
+        let size = match self.size {
+            Expr::Lit(ref lit) => quote!( #lit ),
+            ref other => quote!( (#other) ),
+        };
+
+        Ok(quote! {
+            #krate::__merge_tables! {
+                table #(#attrs)* (#vis) #name [#cell_type; #size] = #(#sources),*
+            }
+        })
+    }
+
+    fn validate(&self) -> Result<()> {
+        let mut errors = Vec::new();
+
+        let mut tables = HashSet::new();
+        let mut flags = HashSet::new();
+        for source in &self.sources {
+            let table = &source.table;
+            if !tables.insert(quote!(#table).to_string()) {
+                errors.push(Error::new_spanned(table,
+                    format!("table `{}` is merged more than once", quote!(#table))));
+            }
+            for flag in &source.flags {
+                if !flags.insert(quote!(#flag).to_string()) {
+                    errors.push(Error::new_spanned(flag,
+                        format!("flag `{}` is merged more than once", quote!(#flag))));
+                }
+            }
+        }
+
+        combine_errors(errors)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn expand(input: TokenStream) -> Result<TokenStream> {
+        syn::parse2::<MergeDef>(input)?.expand()
+    }
+
+    #[test]
+    fn forwards_to_merge_tables_impl() {
+        let out = expand(quote! {
+            /// doc
+            pub(super) struct Table {
+                static data: [u16; 4] = super::Tab1 { A11, A12 } + Tab2 { A21 };
+            }
+        }).unwrap().to_string();
+
+        assert!(out.starts_with(":: lut :: __merge_tables !"));
+        assert!(out.contains("(pub (super)) Table [u16 ; 4] = super :: Tab1 [A11 , A12] , Tab2 [A21]"));
+    }
+
+    #[test]
+    fn custom_crate_path() {
+        let out = expand(quote! {
+            crate = ::my_lut;
+            /// doc
+            struct Table {
+                static data: [u16; 4] = Tab1 { A11 };
+            }
+        }).unwrap().to_string();
+        assert!(out.starts_with(":: my_lut :: __merge_tables !"), "{}", out);
+    }
+
+    #[test]
+    fn duplicate_flag() {
+        let err = expand(quote! {
+            struct Table {
+                static data: [u16; 4] = Tab1 { A11 } + Tab2 { A21, A11 };
+            }
+        }).unwrap_err();
+        assert_eq!(err.to_string(), "flag `A11` is merged more than once");
+    }
+
+    #[test]
+    fn duplicate_table() {
+        let err = expand(quote! {
+            struct Table {
+                static data: [u16; 4] = Tab1 { A11 } + Tab1 { A12 };
+            }
+        }).unwrap_err();
+        assert_eq!(err.to_string(), "table `Tab1` is merged more than once");
+    }
+}
@@ -0,0 +1,478 @@
+use std::collections::HashSet;
+
+use proc_macro2::TokenStream;
+use syn::{
+    Attribute, Error, Expr, ExprLit, GenericArgument, Ident, Lit, Path, PathArguments, Result,
+    Type, Visibility
+};
+use syn::token;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+
+use crate::{combine_errors, parse_crate_path};
+
+mod kw {
+    custom_keyword!(flags);
+}
+
+/// the input of `new_table!`
+pub struct TableDef {
+    krate: Path,
+    flags_vis: Visibility,
+    flags: Vec<FlagDef>,
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    name: Ident,
+    cell_type: Type,
+    size: Expr,
+    data: Data,
+}
+
+struct FlagDef {
+    attrs: Vec<Attribute>,
+    name: Ident,
+    short_name: Ident,
+}
+
+enum Cell {
+    Empty(Token![-]),
+    Flags(Vec<Ident>),
+}
+
+enum Data {
+    Cells(Vec<Cell>),
+    Sets(Vec<SetDef>),
+}
+
+/// the definition of a flag through a set or a predicate
+struct SetDef {
+    short_name: Ident,
+    def: TokenStream,
+}
+
+impl Parse for TableDef {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let krate = parse_crate_path(input)?;
+        let flags_vis = input.parse()?;
+        input.parse::<kw::flags>()?;
+        let flags_content;
+        braced!(flags_content in input);
+        let flags = flags_content.parse_terminated(FlagDef::parse, Token![,])?;
+
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<Token![struct]>()?;
+        let name = input.parse()?;
+
+        let body;
+        braced!(body in input);
+        body.parse::<Token![static]>()?;
+        body.parse::<Ident>()?;
+        body.parse::<Token![:]>()?;
+        let array_type;
+        bracketed!(array_type in body);
+        let cell_type = array_type.parse()?;
+        array_type.parse::<Token![;]>()?;
+        let size = array_type.parse()?;
+        body.parse::<Token![=]>()?;
+        let data = if body.peek(token::Brace) {
+            let sets_content;
+            braced!(sets_content in body);
+            let sets = sets_content.parse_terminated(SetDef::parse, Token![,])?;
+            Data::Sets(sets.into_iter().collect())
+        } else {
+            let cells_content;
+            bracketed!(cells_content in body);
+            let cells = cells_content.parse_terminated(Cell::parse, Token![,])?;
+            Data::Cells(cells.into_iter().collect())
+        };
+        body.parse::<Token![;]>()?;
+
+        Ok(TableDef {
+            krate,
+            flags_vis,
+            flags: flags.into_iter().collect(),
+            attrs, vis, name, cell_type, size, data,
+        })
+    }
+}
+
+impl Parse for FlagDef {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let short_name = input.parse()?;
+        Ok(FlagDef { attrs, name, short_name })
+    }
+}
+
+impl Parse for Cell {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(Token![-]) {
+            return Ok(Cell::Empty(input.parse()?));
+        }
+        if !input.peek(Ident) {
+            return Err(input.error("expected `-` or flag short names separated by `|`"));
+        }
+        let flags = Punctuated::<Ident, Token![|]>::parse_separated_nonempty(input)?;
+        Ok(Cell::Flags(flags.into_iter().collect()))
+    }
+}
+
+impl Parse for SetDef {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let short_name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let def = if input.peek(Token![fn]) {
+            let fn_token = input.parse::<Token![fn]>()?;
+            let path = input.parse::<Path>()?;
+            quote!( #fn_token #path )
+        } else if input.peek(token::Bracket) {
+            let items_content;
+            let bracket = bracketed!(items_content in input);
+            let items = items_content.parse_terminated(SetItem::parse, Token![,])?;
+            let items = items.into_iter().map(|SetItem { start, end }| match end {
+                Some(end) => quote!( #start ..= #end ),
+                None => quote!( #start )
+            });
+            let mut def = TokenStream::new();
+            bracket.surround(&mut def, |def| def.extend(quote!( #(#items),* )));
+            def
+        } else {
+            return Err(input.error(
+                "expected a set like `['a', '0'..='9']` or `fn` followed by a predicate"));
+        };
+        Ok(SetDef { short_name, def })
+    }
+}
+
+/// a literal or an inclusive range of literals in a set
+struct SetItem {
+    start: Lit,
+    end: Option<Lit>,
+}
+
+impl Parse for SetItem {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let start = input.parse()?;
+        let end = if input.peek(Token![..=]) {
+            input.parse::<Token![..=]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(SetItem { start, end })
+    }
+}
+
+impl TableDef {
+
+    pub fn expand(&self) -> Result<TokenStream> {
+        self.validate()?;
+
+        let TableDef { ref krate, ref flags_vis, ref attrs, ref vis, ref name, ref cell_type, .. } = *self;
+        let flags = self.flags.iter().map(|flag| {
+            let FlagDef { ref attrs, ref name, ref short_name } = *flag;
+            quote!( #(#attrs)* #name = #short_name )
+        });
+        let data = match self.data {
+            Data::Cells(ref cells) => {
+                let cells = cells.iter().map(|cell| match *cell {
+                    Cell::Empty(ref dash) => quote!( (#dash) ),
+                    Cell::Flags(ref flags) => quote!( (#(#flags)|*) ),
+                });
+                quote!( [#(#cells),*] )
+            },
+            Data::Sets(ref sets) => {
+                let sets = sets.iter().map(|set| {
+                    let SetDef { ref short_name, ref def } = *set;
+                    quote!( #short_name = #def )
+                });
+                quote!( {#(#sets),*} )
+            }
+        };
+        // the size is forwarded as a single token tree
+        let size = match self.size {
+            Expr::Lit(ref lit) => quote!( #lit ),
+            ref other => quote!( (#other) ),
+        };
+
+        Ok(quote! {
+            #krate::__new_table! {
+                (#flags_vis) flags [#(#flags),*],
+                (#vis) table #(#attrs)* #name [#cell_type; #size] = #data
+            }
+        })
+    }
+
+    fn validate(&self) -> Result<()> {
+        let mut errors = Vec::new();
+
+        let mut names = HashSet::new();
+        let mut short_names = HashSet::new();
+        for flag in &self.flags {
+            if !names.insert(flag.name.to_string()) {
+                errors.push(Error::new(flag.name.span(),
+                    format!("duplicate flag `{}`", flag.name)));
+            }
+            if !short_names.insert(flag.short_name.to_string()) {
+                errors.push(Error::new(flag.short_name.span(),
+                    format!("duplicate flag short name `{}`", flag.short_name)));
+            }
+        }
+
+        if let Some(max) = max_flag_count(&self.cell_type) {
+            if let Some(flag) = self.flags.get(max) {
+                let cell_type = &self.cell_type;
+                errors.push(Error::new(flag.name.span(), format!(
+                    "too many flags, cell type `{}` can hold at most {} flags",
+                    quote!(#cell_type), max
+                )));
+            }
+        }
+
+        let used_short_names = match self.data {
+            Data::Cells(ref cells) => cells.iter()
+                .flat_map(|cell| match *cell {
+                    Cell::Empty(_) => &[][..],
+                    Cell::Flags(ref flags) => &flags[..],
+                })
+                .collect::<Vec<_>>(),
+            Data::Sets(ref sets) => sets.iter().map(|set| &set.short_name).collect()
+        };
+        for flag in used_short_names {
+            if !short_names.contains(&flag.to_string()) {
+                errors.push(Error::new(flag.span(), format!(
+                    "unknown flag short name `{}`, expected one of: {}",
+                    flag, self.short_name_list()
+                )));
+            }
+        }
+
+        match (&self.data, literal_size(&self.size)) {
+            (Data::Cells(cells), Some(size)) => {
+                if cells.len() > size {
+                    let span = match cells[size] {
+                        Cell::Empty(ref dash) => dash.spans[0],
+                        Cell::Flags(ref flags) => flags[0].span(),
+                    };
+                    errors.push(Error::new(span, format!(
+                        "too many cells, the table has a size of {}", size)));
+                } else if cells.len() < size {
+                    errors.push(Error::new_spanned(&self.size, format!(
+                        "the table has a size of {} but only {} cells are given",
+                        size, cells.len()
+                    )));
+                }
+            },
+            (Data::Sets(sets), _) => {
+                let mut defined = HashSet::new();
+                for set in sets {
+                    if !defined.insert(set.short_name.to_string()) {
+                        errors.push(Error::new(set.short_name.span(), format!(
+                            "flag short name `{}` is defined more than once", set.short_name)));
+                    }
+                }
+            },
+            _ => ()
+        }
+
+        combine_errors(errors)
+    }
+
+    fn short_name_list(&self) -> String {
+        let names = self.flags.iter()
+            .map(|flag| flag.short_name.to_string())
+            .collect::<Vec<_>>();
+        if names.is_empty() {
+            "`-`".to_owned()
+        } else {
+            names.join(", ")
+        }
+    }
+}
+
+/// returns the max flag count for the build-in cell types
+pub fn max_flag_count(cell_type: &Type) -> Option<usize> {
+    let path = match *cell_type {
+        Type::Path(ref path) if path.qself.is_none() => &path.path,
+        _ => return None
+    };
+    let segment = path.segments.last()?;
+    if segment.ident == "BitSet" {
+        return match segment.arguments {
+            PathArguments::AngleBracketed(ref args) if args.args.len() == 1 => {
+                match args.args[0] {
+                    GenericArgument::Const(ref words) => literal_size(words).map(|words| words * 64),
+                    _ => None
+                }
+            },
+            _ => None
+        };
+    }
+    let ident = path.get_ident()?;
+    match &*ident.to_string() {
+        "u8" => Some(8),
+        "u16" => Some(16),
+        "u32" => Some(32),
+        "u64" => Some(64),
+        "u128" => Some(128),
+        _ => None
+    }
+}
+
+fn literal_size(size: &Expr) -> Option<usize> {
+    match *size {
+        Expr::Lit(ExprLit { lit: Lit::Int(ref int), .. }) => int.base10_parse().ok(),
+        _ => None
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn expand(input: TokenStream) -> Result<TokenStream> {
+        syn::parse2::<TableDef>(input)?.expand()
+    }
+
+    fn error_message(input: TokenStream) -> String {
+        match expand(input) {
+            Ok(_) => panic!("expected expansion to fail"),
+            Err(err) => err.into_iter()
+                .map(|err| err.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+
+    #[test]
+    fn forwards_to_new_table_impl() {
+        let out = expand(quote! {
+            pub(in crate::foo) flags { A1 = A, A2 = B }
+            /// doc
+            pub struct Table {
+                static data: [u8; 3] = [A, A|B, -];
+            }
+        }).unwrap().to_string();
+
+        assert!(out.starts_with(":: lut :: __new_table !"));
+        assert!(out.contains("(pub (in crate :: foo)) flags [A1 = A , A2 = B]"));
+        assert!(out.contains("[(A) , (A | B) , (-)]"));
+    }
+
+    #[test]
+    fn custom_crate_path() {
+        let out = expand(quote! {
+            crate = crate;
+            flags { A1 = A }
+            struct Table {
+                static data: [u8; 1] = [A];
+            }
+        }).unwrap().to_string();
+        assert!(out.starts_with("crate :: __new_table !"), "{}", out);
+    }
+
+    #[test]
+    fn empty_table() {
+        assert!(expand(quote! {
+            flags {}
+            struct Table {
+                static data: [u8; 0] = [];
+            }
+        }).is_ok());
+    }
+
+    #[test]
+    fn unknown_short_name() {
+        let msg = error_message(quote! {
+            flags { A1 = A }
+            struct Table {
+                static data: [u8; 2] = [A, A|C];
+            }
+        });
+        assert_eq!(msg, "unknown flag short name `C`, expected one of: A");
+    }
+
+    #[test]
+    fn too_many_flags() {
+        let msg = error_message(quote! {
+            flags { F1=F1, F2=F2, F3=F3, F4=F4, F5=F5, F6=F6, F7=F7, F8=F8, F9=F9 }
+            struct Table {
+                static data: [u8; 1] = [F9];
+            }
+        });
+        assert_eq!(msg, "too many flags, cell type `u8` can hold at most 8 flags");
+    }
+
+    #[test]
+    fn too_many_flags_for_bit_set() {
+        let max = max_flag_count(&parse_quote!(::lut::BitSet<2>));
+        assert_eq!(max, Some(128));
+        assert_eq!(max_flag_count(&parse_quote!(BitSet<N>)), None);
+        assert_eq!(max_flag_count(&parse_quote!(usize)), None);
+    }
+
+    #[test]
+    fn cell_count_mismatch() {
+        let msg = error_message(quote! {
+            flags { A1 = A }
+            struct Table {
+                static data: [u8; 2] = [A];
+            }
+        });
+        assert_eq!(msg, "the table has a size of 2 but only 1 cells are given");
+
+        let msg = error_message(quote! {
+            flags { A1 = A }
+            struct Table {
+                static data: [u8; 1] = [A, -];
+            }
+        });
+        assert_eq!(msg, "too many cells, the table has a size of 1");
+    }
+
+    #[test]
+    fn forwards_sets() {
+        let out = expand(quote! {
+            flags { A1 = A, A2 = B }
+            struct Table {
+                static data: [u8; 128] = { A = ['a'..='z', b'_'], B = fn is_b, };
+            }
+        }).unwrap().to_string();
+        assert!(out.contains("= { A = ['a' ..= 'z' , b'_'] , B = fn is_b }"));
+    }
+
+    #[test]
+    fn invalid_sets() {
+        let msg = error_message(quote! {
+            flags { A1 = A }
+            struct Table {
+                static data: [u8; 128] = { A = ['a'], C = ['b'], A = fn is_a };
+            }
+        });
+        assert_eq!(msg, "unknown flag short name `C`, expected one of: A\n\
+            flag short name `A` is defined more than once");
+
+        let msg = error_message(quote! {
+            flags { A1 = A }
+            struct Table {
+                static data: [u8; 128] = { A = is_a };
+            }
+        });
+        assert_eq!(msg, "expected a set like `['a', '0'..='9']` or `fn` followed by a predicate");
+    }
+
+    #[test]
+    fn duplicate_flags_are_all_reported() {
+        let msg = error_message(quote! {
+            flags { A1 = A, A1 = A }
+            struct Table {
+                static data: [u8; 1] = [A];
+            }
+        });
+        assert_eq!(msg, "duplicate flag `A1`\nduplicate flag short name `A`");
+    }
+}
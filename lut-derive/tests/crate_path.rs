@@ -0,0 +1,38 @@
+// the dependency is available under another name, so `::lut` does not resolve
+extern crate lut as lookup;
+
+use lookup::Table;
+use lookup::derive::{new_table, merge_tables};
+
+new_table! {
+    crate = ::lookup;
+    flags { Digit = DI }
+    struct Digits {
+        static data: [u8; 4] = [ DI, DI, -, - ];
+    }
+}
+
+new_table! {
+    crate = ::lookup;
+    flags { Alpha = AL }
+    struct Alphas {
+        static data: [u8; 4] = { AL = [2..=3] };
+    }
+}
+
+merge_tables! {
+    crate = ::lookup;
+    /// documented
+    struct Alnum {
+        static data: [u16; 4] = Digits { Digit } + Alphas { Alpha };
+    }
+}
+
+#[test]
+fn renamed_crate() {
+    assert!(Digits::check_at(1, Digit));
+    assert!(!Digits::check_at(2, Digit));
+    assert!(Alnum::check_at(0, Digit));
+    assert!(Alnum::check_at(3, Alpha));
+    assert!(!Alnum::check_at(3, Digit));
+}
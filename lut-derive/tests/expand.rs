@@ -0,0 +1,107 @@
+extern crate lut;
+
+use lut::Table;
+use lut::derive::{new_table, merge_tables};
+
+new_table! {
+    pub flags { A11 = A1, A12 = A2 }
+    /// documented
+    pub struct Tab1 {
+        static data: [u8; 4] = [
+            A1, A1|A2, -, -
+        ];
+    }
+}
+
+new_table! {
+    flags { A21 = X }
+    struct Tab2 {
+        static data: [u8; 4] = [ X, -, X, - ];
+    }
+}
+
+merge_tables! {
+    struct Tab12 {
+        static data: [u8; 4]
+            = Tab1 { A11, A12 }
+            + Tab2 { A21 };
+    }
+}
+
+mod visibility {
+    pub mod inner {
+        use lut::derive::new_table;
+
+        new_table! {
+            pub(in crate::visibility) flags { F1 = F1 }
+            pub(in crate::visibility) struct Table {
+                static data: [u8; 2] = [ F1, - ];
+            }
+        }
+    }
+
+    #[test]
+    fn restricted_visibility() {
+        use lut::Table;
+        assert!(inner::Table::check_at(0, inner::F1));
+        assert!(!inner::Table::check_at(1, inner::F1));
+    }
+}
+
+mod empty {
+    use lut::derive::new_table;
+
+    new_table! {
+        flags {}
+        /// a zero-sized table, not supported by the `macro_rules!` version
+        struct ZeroSized {
+            static data: [u8; 0] = [];
+        }
+    }
+
+    #[test]
+    fn zero_sized_table() {
+        use lut::Table;
+        assert_eq!(ZeroSized::len(), 0);
+    }
+}
+
+mod sets {
+    use lut::derive::new_table;
+
+    const fn is_ws(idx: usize) -> bool {
+        idx == 0x09 || idx == 0x20
+    }
+
+    new_table! {
+        flags { Digit = DI, Ws = WS }
+        struct Chars {
+            static data: [u8; 128] = { DI = ['0'..='9'], WS = fn is_ws };
+        }
+    }
+
+    #[test]
+    fn table_from_sets() {
+        use lut::Table;
+        assert!(Chars::check_all("0123456789", Digit));
+        assert!(Chars::check_all("\t ", Ws));
+        assert!(!Chars::check_at(b'a' as usize, Digit | Ws));
+    }
+}
+
+#[test]
+fn new_table_lookup() {
+    assert_eq!(Tab1::len(), 4);
+    assert_eq!(Tab1::lookup(0), 0b01);
+    assert_eq!(Tab1::lookup(1), 0b11);
+    assert_eq!(Tab1::lookup(2), 0b00);
+}
+
+#[test]
+fn merged_table_lookup() {
+    assert_eq!(Tab12::lookup(0), 0b0011);
+    assert_eq!(Tab12::lookup(1), 0b0110);
+    assert_eq!(Tab12::lookup(2), 0b0001);
+    assert_eq!(Tab12::lookup(3), 0b0000);
+    assert!(Tab12::check_at(1, A12));
+}
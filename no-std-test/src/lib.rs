@@ -0,0 +1,71 @@
+//! checks that `lut` and the code generated by its macros builds without `std`
+//!
+//! If this crate compiles the macros only refer to `core` paths. The tests
+//! link `std` for the test harness only.
+#![no_std]
+
+#[cfg(test)]
+extern crate std;
+
+#[macro_use]
+extern crate lut;
+
+use lut::{Table, Any, All, ByteClass, ByteScanner};
+
+new_table! {
+    pub flags { Digit=DI, Hex=HX }
+    pub struct Digits {
+        static data: [u8; 8] = [ DI|HX, DI|HX, DI|HX, HX, HX, -, -, DI ];
+    }
+}
+
+new_table! {
+    pub flags { Upper=UP }
+    pub struct Letters {
+        static data: [u8; 4] = [ UP, -, UP, - ];
+    }
+}
+
+merge_tables! {
+    pub struct Merged {
+        static data: [u16; 8] = Digits { Digit, Hex } + Letters { Upper };
+    }
+}
+
+accessor_any!{ pub DigitOrUpper = Digit | Upper }
+accessor_all!{ pub HexDigit = Digit & Hex }
+
+pub const DIGIT_OR_UPPER: ByteClass = byte_class!(Merged, DigitOrUpper);
+
+pub fn is_hex_digit(bch: u8) -> bool {
+    Digits::unbound_check_at(bch as usize, HexDigit)
+}
+
+pub fn is_upper_or_hex(bch: u8) -> bool {
+    Merged::unbound_check_at(bch as usize, Any::new(Upper) | Hex)
+}
+
+pub fn only_digits_or_upper(input: &[u8]) -> bool {
+    ByteScanner::new(DIGIT_OR_UPPER).check_all(input)
+}
+
+pub fn hex_not_digit(bch: u8) -> bool {
+    Merged::unbound_check_at(bch as usize, All::new(Hex).but_not(Digit))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tables_work_without_std() {
+        assert!(is_hex_digit(1));
+        assert!(!is_hex_digit(3));
+        assert!(!is_hex_digit(200));
+        assert!(is_upper_or_hex(2) && is_upper_or_hex(4) && !is_upper_or_hex(5));
+        assert!(hex_not_digit(3) && !hex_not_digit(0));
+        assert!(only_digits_or_upper(&[0, 1, 2, 7]));
+        assert!(!only_digits_or_upper(&[0, 3]));
+    }
+}
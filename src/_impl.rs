@@ -1,33 +1,94 @@
 
-use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign};
-use std::cmp::Eq;
-use std::marker::PhantomData;
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not};
+use core::cmp::Eq;
+use core::marker::PhantomData;
+
+use flags::{FlagInfo, FlagIter, Flags};
+use indices::IndicesMatching;
+use byte_class::ByteClass;
+use scan::ByteScanner;
+#[cfg(feature = "std")]
+use dump::TableDump;
 
 
 pub trait TableValue:
 Sized
 + Copy
++ 'static
 + BitAnd<Self, Output=Self>
 + BitAndAssign<Self>
 + BitOr<Self, Output=Self>
 + BitOrAssign<Self>
++ Not<Output=Self>
 + Eq
 {
     const MAX_FLAG_COUNT: usize;
     const ZERO: Self;
 }
 
+/// a `TableValue` in which single bits can be set at runtime
+///
+/// This is what `DynTable` and the inspection of flags by their bit index
+/// need besides `TableValue`. It's a separate trait so that implementing
+/// `TableValue` does not require it.
+pub trait CellBit: TableValue {
+    /// returns a value with only the given bit set
+    ///
+    /// `idx` has to be smaller than `MAX_FLAG_COUNT`.
+    fn bit(idx: usize) -> Self;
+}
+
+/// helper to do bit operations on table values in a const context
+///
+/// Operations on `TableValue`s are done through traits, which can not be
+/// used in a const context, so this provides inherent `const fn`s for every
+/// cell type instead. Macros can use this as they know the concrete type.
+#[doc(hidden)]
+pub struct ConstCell<V: TableValue> {
+    mark: PhantomData<V>
+}
+
 macro_rules! impl_tv {
-    ($($uxx:ident($count:tt)),*) => ($(
+    ($($uxx:ident($count:expr)),*) => ($(
         impl TableValue for $uxx {
             const MAX_FLAG_COUNT: usize = $count;
             const ZERO: $uxx = 0;
         }
+
+        impl CellBit for $uxx {
+            #[inline(always)]
+            fn bit(idx: usize) -> $uxx {
+                1 << idx
+            }
+        }
+
+        impl ConstCell<$uxx> {
+            #[inline(always)]
+            pub const fn is_set(value: $uxx, bit: usize) -> bool {
+                (value >> bit) & 1 == 1
+            }
+
+            #[inline(always)]
+            pub const fn set(value: $uxx, bit: usize) -> $uxx {
+                value | (1 << bit)
+            }
+
+            #[inline(always)]
+            pub const fn or(left: $uxx, right: $uxx) -> $uxx {
+                left | right
+            }
+
+            #[inline(always)]
+            pub const fn lowest_bit(value: $uxx) -> usize {
+                value.trailing_zeros() as usize
+            }
+        }
     )*)
 }
 
 impl_tv! {
-    u8(8), u16(16), u32(32), u64(64)
+    u8(8), u16(16), u32(32), u64(64), u128(128),
+    usize(usize::BITS as usize)
 }
 
 pub trait ConstFlagCount {
@@ -41,6 +102,15 @@ impl ConstFlagCount for () {
 pub trait Table: Sized + ConstFlagCount {
     type Value: TableValue;
 
+    /// the cells of the table, this is usable in a const context
+    const DATA: &'static [Self::Value];
+
+    /// metadata about all flags of the table ordered by their bit index
+    ///
+    /// Flags of a source table which were not merged into a merged table
+    /// are not listed, but still occupy their bit.
+    const FLAGS: &'static [FlagInfo];
+
     #[inline]
     fn mask<A: Flag<Self>>(_hint: A) -> Self::Value {
         <A as Flag<Self>>::BIT_MASK
@@ -52,13 +122,154 @@ pub trait Table: Sized + ConstFlagCount {
 
     fn check_at<A: Access<Self>>(idx: usize, accessor: A) -> bool;
 
+    /// like `check_at` but returns false instead of panicking if `idx` is out of range
     #[inline(always)]
     fn unbound_check_at<A: Access<Self>>(idx: usize, accessor: A) -> bool {
-        if idx > Self::len() {
-            false
+        Self::get_checked(idx, accessor).unwrap_or(false)
+    }
+
+    /// like `unbound_check_at` but indexed by the code point of `ch`
+    ///
+    /// This returns false for all chars beyond the table, e.g. all non-ascii
+    /// chars for a table with 128 cells.
+    #[inline(always)]
+    fn unbound_check_char<A: Access<Self>>(ch: char, accessor: A) -> bool {
+        Self::unbound_check_at(ch as usize, accessor)
+    }
+
+    /// returns the cell at `idx` or `None` if `idx` is out of range
+    #[inline(always)]
+    fn try_lookup(idx: usize) -> Option<Self::Value> {
+        if idx < Self::len() {
+            Some(Self::lookup(idx))
         } else {
-            Self::check_at(idx, accessor)
+            None
+        }
+    }
+
+    /// like `check_at` but returns `None` if `idx` is out of range
+    #[inline(always)]
+    fn get_checked<A: Access<Self>>(idx: usize, accessor: A) -> Option<bool> {
+        Self::try_lookup(idx).map(|value| accessor.check(value))
+    }
+
+    /// checks if all bytes of `input` are accepted by `accessor`
+    ///
+    /// Bytes outside of the index range of the table are never accepted.
+    #[inline]
+    fn check_all<B, A>(input: &B, accessor: A) -> bool
+        where B: AsRef<[u8]> + ?Sized, A: Access<Self>
+    {
+        Self::find_first_mismatch(input, accessor).is_none()
+    }
+
+    /// returns the index of the first byte in `input` not accepted by `accessor`
+    #[inline]
+    fn find_first_mismatch<B, A>(input: &B, accessor: A) -> Option<usize>
+        where B: AsRef<[u8]> + ?Sized, A: Access<Self>
+    {
+        let len = Self::len();
+        input.as_ref().iter().position(|&bch| {
+            let idx = bch as usize;
+            idx >= len || !accessor.check(Self::lookup(idx))
+        })
+    }
+
+    /// returns the index of the first byte in `input` accepted by `accessor`
+    #[inline]
+    fn find_first_match<B, A>(input: &B, accessor: A) -> Option<usize>
+        where B: AsRef<[u8]> + ?Sized, A: Access<Self>
+    {
+        let len = Self::len();
+        input.as_ref().iter().position(|&bch| {
+            let idx = bch as usize;
+            idx < len && accessor.check(Self::lookup(idx))
+        })
+    }
+
+    /// returns the length of the longest prefix of `input` accepted by `accessor`
+    #[inline]
+    fn prefix_len<B, A>(input: &B, accessor: A) -> usize
+        where B: AsRef<[u8]> + ?Sized, A: Access<Self>
+    {
+        let input = input.as_ref();
+        Self::find_first_mismatch(input, accessor).unwrap_or(input.len())
+    }
+
+    /// splits `input` into the longest prefix accepted by `accessor` and the rest
+    #[inline]
+    fn split_at_mismatch<A>(input: &[u8], accessor: A) -> (&[u8], &[u8])
+        where A: Access<Self>
+    {
+        input.split_at(Self::prefix_len(input, accessor))
+    }
+
+    /// splits `input` into the longest prefix accepted by `accessor` and the rest
+    ///
+    /// If the first rejected byte is part of a multi-byte utf-8 sequence the
+    /// split point is moved back to the start of that sequence.
+    #[inline]
+    fn split_str_at_mismatch<A>(input: &str, accessor: A) -> (&str, &str)
+        where A: Access<Self>
+    {
+        let mut idx = Self::prefix_len(input, accessor);
+        while !input.is_char_boundary(idx) {
+            idx -= 1;
         }
+        input.split_at(idx)
+    }
+
+    /// prepares a `ByteScanner` for the bytes accepted by `accessor`
+    ///
+    /// This is meant for validating large inputs, which it does vectorised
+    /// if the cpu supports it. It is only available for tables with exactly
+    /// 256 cells, i.e. one for every byte, for other tables it fails to compile:
+    ///
+    /// ```compile_fail
+    /// #[macro_use]
+    /// extern crate lut;
+    /// use lut::Table;
+    ///
+    /// new_table! {
+    ///     flags { Digit=DI }
+    ///     struct Chars {
+    ///         static data: [u8; 128] = { DI = ['0'..='9'] };
+    ///     }
+    /// }
+    ///
+    /// # fn main() {
+    /// Chars::scanner(Digit);
+    /// # }
+    /// ```
+    #[inline]
+    fn scanner<A: Access<Self>>(accessor: A) -> ByteScanner {
+        const { assert!(Self::DATA.len() == 256, "only tables with 256 cells have a scanner") };
+        ByteScanner::new(ByteClass::project::<Self, A>(accessor))
+    }
+
+    /// iterates over all indices whose cells are accepted by `accessor`
+    #[inline]
+    fn indices_matching<A: Access<Self>>(accessor: A) -> IndicesMatching<Self, A> {
+        IndicesMatching::new(accessor)
+    }
+
+    /// renders the table as a human readable grid, see `TableDump`
+    #[cfg(feature = "std")]
+    #[inline]
+    fn dump() -> TableDump<Self> {
+        TableDump::new()
+    }
+
+    /// iterates over the metadata of all flags set in `value`
+    #[inline]
+    fn iter_flags(value: Self::Value) -> FlagIter<Self> {
+        FlagIter::new(value)
+    }
+
+    /// returns the cell at `idx` wrapped to print the names of its flags
+    #[inline]
+    fn flags_at(idx: usize) -> Flags<Self> {
+        Flags::new(Self::lookup(idx))
     }
 
     #[doc(hidden)]
@@ -66,6 +277,14 @@ pub trait Table: Sized + ConstFlagCount {
 }
 
 pub trait Flag<T: Table>: Access<T> {
+    /// the name of the flag
+    const NAME: &'static str;
+    /// the short name of the flag used in the table definition
+    const SHORT_NAME: &'static str;
+    /// the name of the table the flag was defined in
+    const ORIGIN: &'static str;
+    /// the index of the bit representing this flag in the cells of `T`
+    const BIT_INDEX: usize;
     const BIT_MASK: T::Value;
 }
 
@@ -123,18 +342,84 @@ impl<T> Access<T> for NoFlagsSet
 //}
 //```
 ///
+/// # Flags defined by sets
+///
+/// Instead of listing every cell the cells can be computed at compile time
+/// from a definition per flag. A definition is either a list of characters,
+/// bytes or integers and inclusive ranges of them, or `fn` followed by the
+/// path of a `const fn(usize) -> bool` which is called with every index.
+/// Indices in a definition which are outside of the table are ignored.
+///
+/// ```
+/// #[macro_use]
+/// extern crate lut;
+/// use lut::Table;
+///
+/// const fn is_ctl(idx: usize) -> bool {
+///     idx < 0x20 || idx == 0x7F
+/// }
+///
+/// new_table! {
+///     flags { HttpToken=HT, Control=CT }
+///     struct Chars {
+///         static data: [u8; 128] = {
+///             HT = ['!', '#'..='\'', '*', '+', '-', '.', '0'..='9', 'A'..='Z', '^'..='~'],
+///             CT = fn is_ctl
+///         };
+///     }
+/// }
+///
+/// # fn main() {
+/// assert!(Chars::check_all("Content-Type", HttpToken));
+/// assert!(!Chars::check_all("a b", HttpToken));
+/// assert!(Chars::check_at(b'\n' as usize, Control));
+/// # }
+/// ```
+///
+/// # Compile time checks
+///
+/// A table with more flags than its cell type can hold fails to compile:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate lut;
+///
+/// new_table! {
+///     flags { F1=F1, F2=F2, F3=F3, F4=F4, F5=F5, F6=F6, F7=F7, F8=F8, F9=F9 }
+///     struct Table {
+///         static data: [u8; 1] = [ F1 ];
+///     }
+/// }
+/// # fn main() {}
+/// ```
+///
+/// A range in a set definition has exactly one start and one end:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate lut;
+///
+/// new_table! {
+///     flags { Alpha=AL }
+///     struct Table {
+///         static data: [u8; 128] = { AL = ['a'..='b'..='c'] };
+///     }
+/// }
+/// # fn main() {}
+/// ```
+///
 #[macro_export]
 macro_rules! new_table {
     (
         pub flags {$( $(#[$fattr:meta])* $fname:ident = $short_name:ident),*}
         $(#[$attr:meta])*
         pub struct $name:ident {
-            static $_f:ident: [$tp:ty;$size:tt] = [$($($v:tt)|*),*];
+            static $_f:ident: [$tp:ty;$size:tt] = $data:tt;
         }
     ) => (
-        __new_table! {
+        $crate::__new_table! {
             (pub) flags [$( $(#[$fattr])* $fname = $short_name ),*],
-            (pub) table $(#[$attr])* $name [$tp;$size] = [$($($v)|*),*]
+            (pub) table $(#[$attr])* $name [$tp;$size] = $data
         }
     );
 
@@ -142,12 +427,12 @@ macro_rules! new_table {
         pub flags {$( $(#[$fattr:meta])* $fname:ident = $short_name:ident),*}
         $(#[$attr:meta])*
         pub($($vis:tt)+) struct $name:ident {
-            static $_f:ident: [$tp:ty;$size:tt] = [$($($v:tt)|*),*];
+            static $_f:ident: [$tp:ty;$size:tt] = $data:tt;
         }
     ) => (
-        __new_table! {
+        $crate::__new_table! {
             (pub) flags [$( $(#[$fattr])* $fname = $short_name ),*],
-            (pub($($vis)+)) table $(#[$attr])* $name [$tp;$size] = [$($($v)|*),*]
+            (pub($($vis)+)) table $(#[$attr])* $name [$tp;$size] = $data
         }
     );
 
@@ -155,12 +440,12 @@ macro_rules! new_table {
         pub flags {$( $(#[$fattr:meta])* $fname:ident = $short_name:ident),*}
         $(#[$attr:meta])*
         struct $name:ident {
-            static $_f:ident: [$tp:ty;$size:tt] = [$($($v:tt)|*),*];
+            static $_f:ident: [$tp:ty;$size:tt] = $data:tt;
         }
     ) => (
-        __new_table! {
+        $crate::__new_table! {
             (pub) flags [$( $(#[$fattr])* $fname = $short_name),*],
-            () table $(#[$attr])* $name [$tp;$size] = [$($($v)|*),*]
+            () table $(#[$attr])* $name [$tp;$size] = $data
         }
     );
 
@@ -170,12 +455,12 @@ macro_rules! new_table {
         pub($($vis:tt)+) flags {$( $(#[$fattr:meta])* $fname:ident = $short_name:ident),*}
         $(#[$attr:meta])*
         pub struct $name:ident {
-            static $_f:ident: [$tp:ty;$size:tt] = [$($($v:tt)|*),*];
+            static $_f:ident: [$tp:ty;$size:tt] = $data:tt;
         }
     ) => (
-        __new_table! {
+        $crate::__new_table! {
             (pub($($vis)+)) flags [$( $(#[$fattr])* $fname = $short_name),*],
-            (pub) table $(#[$attr])* $name [$tp;$size] = [$($($v)|*),*]
+            (pub) table $(#[$attr])* $name [$tp;$size] = $data
         }
     );
 
@@ -183,12 +468,12 @@ macro_rules! new_table {
         pub($($fvis:tt)+) flags {$( $(#[$fattr:meta])* $fname:ident = $short_name:ident),*}
         $(#[$attr:meta])*
         pub($($tvis:tt)+) struct $name:ident {
-            static $_f:ident: [$tp:ty;$size:tt] = [$($($v:tt)|*),*];
+            static $_f:ident: [$tp:ty;$size:tt] = $data:tt;
         }
     ) => (
-        __new_table! {
+        $crate::__new_table! {
             (pub($($fvis)+)) flags [$( $(#[$fattr])* $fname = $short_name),*],
-            (pub($($tvis)+)) table $(#[$attr])* $name [$tp;$size] = [$($($v)|*),*]
+            (pub($($tvis)+)) table $(#[$attr])* $name [$tp;$size] = $data
         }
     );
 
@@ -196,12 +481,12 @@ macro_rules! new_table {
         pub($($vis:tt)+) flags {$( $(#[$fattr:meta])* $fname:ident = $short_name:ident),*}
         $(#[$attr:meta])*
         struct $name:ident {
-            static $_f:ident: [$tp:ty;$size:tt] = [$($($v:tt)|*),*];
+            static $_f:ident: [$tp:ty;$size:tt] = $data:tt;
         }
     ) => (
-        __new_table! {
+        $crate::__new_table! {
             (pub($($vis)+)) flags [$( $(#[$fattr])* $fname = $short_name),*],
-            () table $(#[$attr])* $name [$tp;$size] = [$($($v)|*),*]
+            () table $(#[$attr])* $name [$tp;$size] = $data
         }
     );
 
@@ -210,36 +495,36 @@ macro_rules! new_table {
         flags {$( $(#[$fattr:meta])* $fname:ident = $short_name:ident),*}
         $(#[$attr:meta])*
         pub struct $name:ident {
-            static $_f:ident: [$tp:ty;$size:tt] = [$($($v:tt)|*),*];
+            static $_f:ident: [$tp:ty;$size:tt] = $data:tt;
         }
     ) => (
-        __new_table! {
+        $crate::__new_table! {
             () flags [$( $(#[$fattr])* $fname = $short_name),*],
-            (pub) table $(#[$attr])* $name [$tp;$size] = [$($($v)|*),*]
+            (pub) table $(#[$attr])* $name [$tp;$size] = $data
         }
     );
     (
         flags {$( $(#[$fattr:meta])* $fname:ident = $short_name:ident),*}
         $(#[$attr:meta])*
         pub($($vis:tt)+) struct $name:ident {
-            static $_f:ident: [$tp:ty;$size:tt] = [$($($v:tt)|*),*];
+            static $_f:ident: [$tp:ty;$size:tt] = $data:tt;
         }
     ) => (
-        __new_table! {
+        $crate::__new_table! {
             () flags [$( $(#[$fattr])* $fname = $short_name),*],
-            (pub($($vis)+)) table $(#[$attr])* $name [$tp;$size] = [$($($v)|*),*]
+            (pub($($vis)+)) table $(#[$attr])* $name [$tp;$size] = $data
         }
     );
     (
         flags {$( $(#[$fattr:meta])* $fname:ident = $short_name:ident),*}
         $(#[$attr:meta])*
         struct $name:ident {
-            static $_f:ident: [$tp:ty;$size:tt] = [$($($v:tt)|*),*];
+            static $_f:ident: [$tp:ty;$size:tt] = $data:tt;
         }
     ) => (
-        __new_table! {
+        $crate::__new_table! {
             () flags [$( $(#[$fattr])* $fname = $short_name),*],
-            () table $(#[$attr])* $name [$tp;$size] = [$($($v)|*),*]
+            () table $(#[$attr])* $name [$tp;$size] = $data
         }
     );
 
@@ -250,10 +535,11 @@ macro_rules! new_table {
 macro_rules! __new_table {
     (
         ($($flag_vis:tt)*) flags [$($(#[$fattr:meta])* $fname:ident = $short_name:ident),*],
-        ($($table_vis:tt)*) table $(#[$attr:meta])* $name:ident [$tp:ty;$size:tt] = [$($($v:tt)|*),*]
+        ($($table_vis:tt)*) table $(#[$attr:meta])* $name:ident [$tp:ty;$size:tt] = $data:tt
     ) => (
 
-        __new_table!{@DEF_FLAGS ($($flag_vis)*) $name [$($(#[$fattr])* $fname),*] [ ]}
+        $crate::__new_table!{@DEF_FLAGS ($($flag_vis)*) $name [$($(#[$fattr])* $fname = $short_name),*] [ ]}
+        $crate::__new_table!{@NOT ($($flag_vis)*) ($($table_vis)*) $name [$($fname),*]}
 
 
         #[derive(Copy, Clone, Debug)]
@@ -261,9 +547,15 @@ macro_rules! __new_table {
         $($table_vis)* struct $name;
 
         impl $crate::ConstFlagCount for $name {
-            const FLAG_COUNT: usize = __new_table!{@COUNT [$($fname),*] []};
+            const FLAG_COUNT: usize = $crate::__new_table!{@COUNT [$($fname),*] []};
         }
 
+        const _: () = assert!(
+            <$name as $crate::ConstFlagCount>::FLAG_COUNT
+                <= <$tp as $crate::TableValue>::MAX_FLAG_COUNT,
+            "the table has more flags than its cell type can hold"
+        );
+
         impl $crate::Table for $name {
             type Value = $tp;
 
@@ -272,15 +564,19 @@ macro_rules! __new_table {
                 $size
             }
 
+            const FLAGS: &'static [$crate::FlagInfo] = &[$($crate::FlagInfo::of::<$name, $fname>()),*];
+
+            const DATA: &'static [$tp] = {
+                $(#[allow(dead_code, clippy::upper_case_acronyms)] type $short_name = self::$fname;)*
+                type S = $name;
+                #[allow(unused_mut, clippy::unnecessary_cast, clippy::absurd_extreme_comparisons)]
+                static TABLE: [$tp;$size] = $crate::__new_table!{@DATA $tp, $size, S, $data};
+                &TABLE
+            };
+
             #[inline(always)]
             fn lookup(idx: usize) -> Self::Value {
-                $(type $short_name = self::$fname;)*
-                type S = $name;
-                type T = $tp;
-                static TABLE: [$tp;$size] = [$(
-                    0$(|<__new_table!{@MAP $v} as $crate::Flag<S>>::BIT_MASK as T)*
-                ),*];
-                TABLE[idx]
+                Self::DATA[idx]
             }
 
             #[inline(always)]
@@ -298,12 +594,49 @@ macro_rules! __new_table {
         // and prevents me from having a wild card implementation for any
         // table
         impl $crate::Flag<$name> for $crate::EmptyFlag {
+            const NAME: &'static str = "-";
+            const SHORT_NAME: &'static str = "-";
+            const ORIGIN: &'static str = "";
+            // the empty flag has no bit, its mask is always zero
+            const BIT_INDEX: usize = usize::MAX;
             const BIT_MASK: $tp = <<$name as $crate::Table>::Value as $crate::TableValue>::ZERO;
         }
     );
+    // the negation of a flag is a forbidden mask of the table it was
+    // defined in, unless the table is less visible than the flags, as the
+    // mask would then be a private type in a public interface
+    (@NOT () ($($table_vis:tt)*) $table:ident [$($fname:ident),*]) => (
+        $crate::__new_table!{@NOT_MASKED $table [$($fname),*]}
+    );
+    (@NOT ($($flag_vis:tt)+) (pub) $table:ident [$($fname:ident),*]) => (
+        $crate::__new_table!{@NOT_MASKED $table [$($fname),*]}
+    );
+    (@NOT (pub(crate)) (pub(crate)) $table:ident [$($fname:ident),*]) => (
+        $crate::__new_table!{@NOT_MASKED $table [$($fname),*]}
+    );
+    (@NOT ($($flag_vis:tt)+) ($($table_vis:tt)*) $table:ident [$($fname:ident),*]) => ($(
+        impl $crate::__core::ops::Not for $fname {
+            type Output = $crate::Not<$fname>;
+
+            #[inline(always)]
+            fn not(self) -> Self::Output {
+                $crate::Not(self)
+            }
+        }
+    )*);
+    (@NOT_MASKED $table:ident [$($fname:ident),*]) => ($(
+        impl $crate::__core::ops::Not for $fname {
+            type Output = $crate::Masked<$table>;
+
+            #[inline(always)]
+            fn not(self) -> Self::Output {
+                $crate::Masked::empty().without(self)
+            }
+        }
+    )*);
     (@DEF_FLAGS ($($flag_vis:tt)*) $t:ident [] [$($inc:tt)*]) => ();
     (@DEF_FLAGS ($($flag_vis:tt)*) $table:ident
-        [ $(#[$hattr:meta])* $head:ident $(, $(#[$tattr:meta])* $tail:ident)*]
+        [ $(#[$hattr:meta])* $head:ident = $hshort:ident $(, $(#[$tattr:meta])* $tail:ident = $tshort:ident)*]
         [$($inc:tt)*]
     ) => (
 
@@ -313,8 +646,14 @@ macro_rules! __new_table {
 
 
         impl $crate::Flag<$table> for $head {
+            const NAME: &'static str = stringify!($head);
+            const SHORT_NAME: &'static str = stringify!($hshort);
+            const ORIGIN: &'static str = stringify!($table);
+            const BIT_INDEX: usize = 0 $(+ $inc)*;
             const BIT_MASK: <$table as $crate::Table>::Value =
-                (1 << (0 $(+ $inc)*)); // as <$table as $crate::Table>::Value;
+                $crate::ConstCell::<<$table as $crate::Table>::Value>::set(
+                    <<$table as $crate::Table>::Value as $crate::TableValue>::ZERO,
+                    <Self as $crate::Flag<$table>>::BIT_INDEX);
         }
 
         impl $crate::Access<$table> for $head {
@@ -324,6 +663,9 @@ macro_rules! __new_table {
             }
         }
 
+        // `From` can't be used here as `T` would be an uncovered
+        // type parameter in a downstream crate
+        #[allow(clippy::from_over_into)]
         impl<T> Into<$crate::All<T>> for $head
             where T: $crate::Table, $head: $crate::Flag<T>
         {
@@ -333,6 +675,7 @@ macro_rules! __new_table {
             }
         }
 
+        #[allow(clippy::from_over_into)]
         impl<T> Into<$crate::Any<T>> for $head
             where T: $crate::Table, $head: $crate::Flag<T>
         {
@@ -342,24 +685,129 @@ macro_rules! __new_table {
             }
         }
 
-        impl ::std::default::Default for $head {
+        impl $crate::__core::default::Default for $head {
             fn default() -> Self {
                 $head
             }
         }
 
-        __new_table!{@DEF_FLAGS ($($flag_vis)*) $table [$( $(#[$tattr])* $tail),*] [$($inc)* 1]}
+        impl<T> $crate::ConstAccess<T> for $head
+            where T: $crate::Table, $head: $crate::Flag<T>
+        {
+            const BITS: &'static [usize] = &[<$head as $crate::Flag<T>>::BIT_INDEX];
+            const REQUIRE_ALL: bool = true;
+        }
+
+        impl<__R> $crate::__core::ops::BitAnd<__R> for $head {
+            type Output = $crate::And<$head, __R>;
+
+            #[inline(always)]
+            fn bitand(self, other: __R) -> Self::Output {
+                $crate::And(self, other)
+            }
+        }
+
+        impl<__R> $crate::__core::ops::BitOr<__R> for $head {
+            type Output = $crate::Or<$head, __R>;
+
+            #[inline(always)]
+            fn bitor(self, other: __R) -> Self::Output {
+                $crate::Or(self, other)
+            }
+        }
+
+        impl<T> $crate::MaskedAnd<T> for $head
+            where T: $crate::Table, $head: $crate::Flag<T>
+        {
+            type Output = $crate::Masked<T>;
+
+            #[inline(always)]
+            fn and_masked(self, masked: $crate::Masked<T>) -> $crate::Masked<T> {
+                masked.with(self)
+            }
+        }
+
+        $crate::__new_table!{@DEF_FLAGS ($($flag_vis)*) $table [$( $(#[$tattr])* $tail = $tshort),*] [$($inc)* 1]}
     );
     (@COUNT [] [$($inc:tt)*]) => (
-        $($inc +)* 0;
+        $($inc +)* 0
     );
     (@COUNT [$head:ident $(, $tail:ident)*] [$($inc:tt)*]) => (
-        __new_table!{@COUNT [$($tail),*] [$($inc)* 1]}
+        $crate::__new_table!{@COUNT [$($tail),*] [$($inc)* 1]}
+    );
+    (@DATA $tp:ty, $size:tt, $s:ident, [$(($($v:tt)|*)),*]) => ([$({
+        let cell = <$tp as $crate::TableValue>::ZERO;
+        $(let cell = $crate::ConstCell::<$tp>::or(
+            cell, <$crate::__new_table!{@MAP $v} as $crate::Flag<$s>>::BIT_MASK);)*
+        cell
+    }),*]);
+    (@DATA $tp:ty, $size:tt, $s:ident, [$($($v:tt)|*),*]) => (
+        $crate::__new_table!{@DATA $tp, $size, $s, [$(($($v)|*)),*]}
+    );
+    (@DATA $tp:ty, $size:tt, $s:ident, {$($sets:tt)*}) => ({
+        let mut table = [<$tp as $crate::TableValue>::ZERO; $size];
+        let mut idx = 0;
+        while idx < $size {
+            let mut cell = <$tp as $crate::TableValue>::ZERO;
+            $crate::__new_table!{@SETS $tp, $s, idx, cell, $($sets)* ,}
+            table[idx] = cell;
+            idx += 1;
+        }
+        table
+    });
+    // every definition is followed by a comma, a trailing comma of the
+    // input leads to an additional one
+    (@SETS $tp:ty, $s:ident, $idx:ident, $cell:ident, $(,)*) => ();
+    (@SETS $tp:ty, $s:ident, $idx:ident, $cell:ident,
+        $short:ident = [$($lo:literal $(..= $hi:literal)?),*], $($rest:tt)*
+    ) => (
+        $crate::__new_table!{@SET $tp, $s, $cell, $short,
+            false $(|| $crate::__new_table!(@IN $idx, $lo $(..= $hi)?))*}
+        $crate::__new_table!{@SETS $tp, $s, $idx, $cell, $($rest)*}
+    );
+    (@SETS $tp:ty, $s:ident, $idx:ident, $cell:ident,
+        $short:ident = fn $pred:path, $($rest:tt)*
+    ) => (
+        $crate::__new_table!{@SET $tp, $s, $cell, $short, $pred($idx)}
+        $crate::__new_table!{@SETS $tp, $s, $idx, $cell, $($rest)*}
     );
+    (@SET $tp:ty, $s:ident, $cell:ident, $short:ident, $($cond:tt)*) => (
+        if $($cond)* {
+            $cell = $crate::ConstCell::<$tp>::or($cell, <$short as $crate::Flag<$s>>::BIT_MASK);
+        }
+    );
+    (@IN $idx:ident, $c:literal) => ($idx == $c as usize);
+    (@IN $idx:ident, $lo:literal ..= $hi:literal) => ($lo as usize <= $idx && $idx <= $hi as usize);
     (@MAP -) => ($crate::EmptyFlag);
     (@MAP $v:tt) => ($v);
 }
 
+/// # Compile time checks
+///
+/// Merging fails to compile if the merged tables have more flags than
+/// the cell type of the new table can hold. Flags of a table which are
+/// not merged are counted too, as the bit positions of the merged flags
+/// are kept:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate lut;
+///
+/// new_table! {
+///     flags { E1=E1, E2=E2, E3=E3, E4=E4, E5=E5, E6=E6, E7=E7, E8=E8, E9=E9 }
+///     struct Table1 {
+///         static data: [u16; 2] = [ E1|E2, E9 ];
+///     }
+/// }
+///
+/// merge_tables! {
+///     struct Table2 {
+///         static data: [u8; 2] = Table1 { E1, E2 };
+///     }
+/// }
+/// # fn main() {}
+/// ```
+///
 /// # Example
 ///
 // ```
@@ -385,7 +833,7 @@ macro_rules! merge_tables {
             )*;
         }
     ) => (
-        __merge_tables! {
+        $crate::__merge_tables! {
             table $(#[$attr])* (pub) $name [$tp;$size] = $first [ $($flag),* ] $(, $next [$($nflag),*])*
         }
     );
@@ -399,7 +847,7 @@ macro_rules! merge_tables {
             )*;
         }
     ) => (
-        __merge_tables! {
+        $crate::__merge_tables! {
             table $(#[$attr])* (pub($($vis)+)) $name [$tp;$size] = $first [ $($flag),* ] $(, $next [$($nflag),*])*
         }
     );
@@ -413,7 +861,7 @@ macro_rules! merge_tables {
             )*;
         }
     ) => (
-        __merge_tables! {
+        $crate::__merge_tables! {
             table $(#[$attr])* () $name [$tp;$size] = $first [ $($flag),* ] $(, $next [$($nflag),*])*
         }
     );
@@ -424,7 +872,7 @@ macro_rules! merge_tables {
 macro_rules! __merge_tables {
     (table $(#[$attr:meta])* ($($vis:tt)*) $name:ident [$tp:ty;$size:tt] = $($ct:ty [$($cf:ty),*]),*) => (
 
-        #[derive(Copy, Clone)]
+        #[derive(Copy, Clone, Debug)]
         $(#[$attr])*
         $($vis)* struct $name;
 
@@ -432,6 +880,14 @@ macro_rules! __merge_tables {
             const FLAG_COUNT: usize = 0 $(+ <$ct as $crate::ConstFlagCount>::FLAG_COUNT)*;
         }
 
+        // all flags of a merged table are counted, even if not all are
+        // merged, as the bit positions of the merged flags are kept
+        const _: () = assert!(
+            <$name as $crate::ConstFlagCount>::FLAG_COUNT
+                <= <$tp as $crate::TableValue>::MAX_FLAG_COUNT,
+            "the merged tables have more flags than the cell type can hold"
+        );
+
         impl $crate::Table for $name {
             type Value = $tp;
 
@@ -440,22 +896,53 @@ macro_rules! __merge_tables {
                 $size
             }
 
+            const FLAGS: &'static [$crate::FlagInfo] = {
+                const BY_BIT: [Option<$crate::FlagInfo>; <$name as $crate::ConstFlagCount>::FLAG_COUNT] = {
+                    let mut by_bit = [None; <$name as $crate::ConstFlagCount>::FLAG_COUNT];
+                    $($(
+                        by_bit[<$cf as $crate::Flag<$name>>::BIT_INDEX] =
+                            Some($crate::FlagInfo::of::<$name, $cf>());
+                    )*)*
+                    by_bit
+                };
+                // drops the bits of flags which were not merged
+                const FLAGS: [$crate::FlagInfo; [$($(stringify!($cf),)*)*].len()] = {
+                    let mut flags = [$crate::FlagInfo { name: "", short_name: "", bit_index: 0, table: "" };
+                        [$($(stringify!($cf),)*)*].len()];
+                    let mut bit = 0;
+                    let mut idx = 0;
+                    while bit < BY_BIT.len() {
+                        if let Some(flag) = BY_BIT[bit] {
+                            flags[idx] = flag;
+                            idx += 1;
+                        }
+                        bit += 1;
+                    }
+                    flags
+                };
+                &FLAGS
+            };
+
+            const DATA: &'static [$tp] = {
+                static TABLE: [$tp;$size] = {
+                    let mut res = [<$tp as $crate::TableValue>::ZERO;$size];
+                    let mut idx = 0;
+                    while idx < $size {
+                        $crate::__merge_tables!{ @MERGE_ITER_STEP
+                            $tp, idx, res, $name,
+                            $crate::FCSum<(), ()>,
+                            [$($ct [$($cf)*])*]
+                        }
+                        idx += 1;
+                    }
+                    res
+                };
+                &TABLE
+            };
+
             #[inline(always)]
             fn lookup(idx: usize) -> Self::Value {
-                lazy_static! {
-                    static ref TABLE: [$tp;$size] = {
-                        let mut res = [<$tp as Default>::default();$size];
-                        for (idx, field) in res.iter_mut().enumerate() {
-                            __merge_tables!{ @MERGE_ITER_STEP
-                                $tp, idx, field, $name,
-                                $crate::FCSum<(), ()>,
-                                [$($ct [$($cf)*])*]
-                            }
-                        }
-                        res
-                    };
-                }
-                TABLE[idx]
+                Self::DATA[idx]
             }
 
             #[inline(always)]
@@ -470,7 +957,7 @@ macro_rules! __merge_tables {
         }
 
 
-        __merge_tables! {@MERGE_FLAG_IMPL
+        $crate::__merge_tables! {@MERGE_FLAG_IMPL
             $name, $tp, $name,
             $crate::FCSum<(),()>,
             [ $($ct [$($cf)*])* ] }
@@ -485,15 +972,16 @@ macro_rules! __merge_tables {
         //              ...FCSum<TableX, FCSum<(),()>>...>>
         $(
             impl $crate::Flag<$new_table> for $current_flag {
-                const BIT_MASK: $tp =
-                    //FIXME we need a `as $tp` to go from u8->u16 etc. but sadly it
-                    // also allows the other way around u16-u8
-                    // we might be able to do some tricks with having a nop bit shift (which is optimized
-                    // out) which is larger, as a >8 for u8, ... bit shift _does fail at compiler time_
-                    // just when we shift less but our mask is bigger it hits
-                    (<$current_flag as $crate::Flag<$current_table>>::BIT_MASK as $tp)
-                    << (<$fc_total as $crate::ConstFlagCount>::FLAG_COUNT
-                        - <$crate::FCSum<$current_table, $fc_prev> as $crate::ConstFlagCount>::FLAG_COUNT);
+                const NAME: &'static str = <$current_flag as $crate::Flag<$current_table>>::NAME;
+                const SHORT_NAME: &'static str =
+                    <$current_flag as $crate::Flag<$current_table>>::SHORT_NAME;
+                const ORIGIN: &'static str = <$current_flag as $crate::Flag<$current_table>>::ORIGIN;
+                const BIT_INDEX: usize = <$current_flag as $crate::Flag<$current_table>>::BIT_INDEX
+                    + <$fc_total as $crate::ConstFlagCount>::FLAG_COUNT
+                    - <$crate::FCSum<$current_table, $fc_prev> as $crate::ConstFlagCount>::FLAG_COUNT;
+                const BIT_MASK: $tp = $crate::ConstCell::<$tp>::set(
+                    <$tp as $crate::TableValue>::ZERO,
+                    <Self as $crate::Flag<$new_table>>::BIT_INDEX);
             }
             impl $crate::Access<$new_table> for $current_flag {
                 #[inline(always)]
@@ -503,34 +991,35 @@ macro_rules! __merge_tables {
                 }
             }
         )*
-        __merge_tables!{ @MERGE_FLAG_IMPL
+        $crate::__merge_tables!{ @MERGE_FLAG_IMPL
             $new_table, $tp, $fc_total,
             $crate::FCSum<$current_table, $fc_prev>,
             [ $($tail_t [ $($tail_f)* ])* ] }
     );
-    ( @MERGE_ITER_STEP $tp:ty, $idx:ident, $field:ident, $total_fc:ident, $fc_prev:ty, []) => ();
-    ( @MERGE_ITER_STEP $tp:ty, $idx:ident, $field:ident, $total_fc:ident,
+    ( @MERGE_ITER_STEP $tp:ty, $idx:ident, $res:ident, $total_fc:ident, $fc_prev:ty, []) => ();
+    ( @MERGE_ITER_STEP $tp:ty, $idx:ident, $res:ident, $total_fc:ident,
         $fc_prev:ty,
         [ $current_table:ty [ $($current_flag:ty)* ] $($tail_t:ty [$($tail_f:ty)*])* ]
     ) => (
-        {$(
-            if <$current_table as $crate::Table>::check_flag_at::<$current_flag>($idx) {
-                *$field = *$field | (
-                    <$current_flag as $crate::Flag<$current_table>>::BIT_MASK
-                    << (<$total_fc as $crate::ConstFlagCount>::FLAG_COUNT
-                        - <$crate::FCSum<$current_table, $fc_prev> as $crate::ConstFlagCount>::FLAG_COUNT)
-                ) as $tp;
+        // cells past the end of a smaller source table are treated as empty
+        if $idx < <$current_table as $crate::Table>::DATA.len() {$(
+            let bit = <$current_flag as $crate::Flag<$current_table>>::BIT_INDEX;
+            let cell = <$current_table as $crate::Table>::DATA[$idx];
+            if $crate::ConstCell::<<$current_table as $crate::Table>::Value>::is_set(cell, bit) {
+                $res[$idx] = $crate::ConstCell::<$tp>::set($res[$idx], bit
+                    + <$total_fc as $crate::ConstFlagCount>::FLAG_COUNT
+                    - <$crate::FCSum<$current_table, $fc_prev> as $crate::ConstFlagCount>::FLAG_COUNT);
             }
         )*}
-        __merge_tables! { @MERGE_ITER_STEP
-            $tp, $idx, $field, $total_fc,
+        $crate::__merge_tables! { @MERGE_ITER_STEP
+            $tp, $idx, $res, $total_fc,
             $crate::FCSum<$current_table, $fc_prev>,
             [$($tail_t [$($tail_f)*])*]
         }
     );
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct Any<T: Table> {
     mask: T::Value
 }
@@ -590,7 +1079,7 @@ impl<T> Access<T> for Any<T>
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct All<T: Table> {
     mask: T::Value
 }
@@ -624,6 +1113,8 @@ impl<T, I: Into<All<T>>> BitAnd<I> for All<T>
 {
     type Output = Self;
 
+    // requiring all flags of both sides means joining their masks
+    #[allow(clippy::suspicious_arithmetic_impl)]
     #[inline(always)]
     fn bitand(self, other: I) -> Self {
         All { mask: self.mask | other.into().mask }
@@ -634,6 +1125,7 @@ impl<T, I: Into<All<T>>> BitAndAssign<I> for All<T>
     where T: Table
 {
 
+    #[allow(clippy::suspicious_op_assign_impl)]
     #[inline(always)]
     fn bitand_assign(&mut self, other: I) {
         self.mask |=  other.into().mask
@@ -652,23 +1144,24 @@ impl<T> Access<T> for All<T>
 
 #[macro_export]
 macro_rules! accessor_all {
-    (pub $name:ident = $($subname:ident)&+) => (
-        accessor_all!{ @IMPL
-            (pub) $name = $($subname)&+
+    ($(#[$attr:meta])* pub $name:ident = $($subname:ident)&+) => (
+        $crate::accessor_all!{ @IMPL
+            $(#[$attr])* (pub) $name = $($subname)&+
         }
     );
-    (pub($($vis:tt)*) $name:ident = $($subname:ident)&+) => (
-        accessor_all!{ @IMPL
-            (pub($($vis)*)) $name = $($subname)&+
+    ($(#[$attr:meta])* pub($($vis:tt)*) $name:ident = $($subname:ident)&+) => (
+        $crate::accessor_all!{ @IMPL
+            $(#[$attr])* (pub($($vis)*)) $name = $($subname)&+
         }
     );
-    ($name:ident = $($subname:ident)&+) => (
-        accessor_all!{ @IMPL
-            () $name = $($subname)&+
+    ($(#[$attr:meta])* $name:ident = $($subname:ident)&+) => (
+        $crate::accessor_all!{ @IMPL
+            $(#[$attr])* () $name = $($subname)&+
         }
     );
-    (@IMPL ($($vis:tt)*) $name:ident = $($subname:ident)&+) => (
+    (@IMPL $(#[$attr:meta])* ($($vis:tt)*) $name:ident = $($subname:ident)&+) => (
         #[derive(Copy, Clone, Debug)]
+        $(#[$attr])*
         $($vis)* struct $name;
         impl<T: $crate::Table> $crate::Access<T> for $name
                   where $($subname: $crate::Flag<T>),*
@@ -679,28 +1172,46 @@ macro_rules! accessor_all {
                 value & mask == mask
             }
         }
-        impl ::std::default::Default for $name {
+        impl<T: $crate::Table> $crate::ConstAccess<T> for $name
+                  where $($subname: $crate::Flag<T>),*
+        {
+            const BITS: &'static [usize] = &[$(<$subname as $crate::Flag<T>>::BIT_INDEX),+];
+            const REQUIRE_ALL: bool = true;
+        }
+        impl<T: $crate::Table> $crate::MaskedAnd<T> for $name
+                  where $($subname: $crate::Flag<T>),*
+        {
+            type Output = $crate::Masked<T>;
+
+            #[inline(always)]
+            fn and_masked(self, masked: $crate::Masked<T>) -> $crate::Masked<T> {
+                masked.with($crate::All::from_mask($(<$subname as $crate::Flag<T>>::BIT_MASK)|*))
+            }
+        }
+        impl $crate::__core::default::Default for $name {
             fn default() -> Self {
                 $name
             }
         }
+
+        $crate::__impl_access_ops!{ [] $name }
     )
 }
 
 #[macro_export]
 macro_rules! accessor_any {
     ($(#[$attr:meta])* pub $name:ident = $($subname:ident)|+) => (
-        accessor_any!{ @IMPL
+        $crate::accessor_any!{ @IMPL
             $(#[$attr])* (pub) $name = $($subname)|+
         }
     );
     ($(#[$attr:meta])* pub($($vis:tt)*) $name:ident = $($subname:ident)|+) => (
-        accessor_any!{ @IMPL
+        $crate::accessor_any!{ @IMPL
             $(#[$attr])* (pub($($vis)*)) $name = $($subname)|+
         }
     );
     ($(#[$attr:meta])* $name:ident = $($subname:ident)|+) => (
-        accessor_any!{ @IMPL
+        $crate::accessor_any!{ @IMPL
             $(#[$attr])* () $name = $($subname)|+
         }
     );
@@ -718,17 +1229,37 @@ macro_rules! accessor_any {
             }
         }
 
-        impl ::std::default::Default for $name {
+        impl<T: $crate::Table> $crate::ConstAccess<T> for $name
+                  where $($subname: $crate::Flag<T>),*
+        {
+            const BITS: &'static [usize] = &[$(<$subname as $crate::Flag<T>>::BIT_INDEX),+];
+            const REQUIRE_ALL: bool = false;
+        }
+
+        impl<T: $crate::Table> $crate::MaskedAnd<T> for $name {
+            type Output = $crate::And<$crate::Masked<T>, $name>;
+
+            #[inline(always)]
+            fn and_masked(self, masked: $crate::Masked<T>) -> Self::Output {
+                $crate::And(masked, self)
+            }
+        }
+
+        impl $crate::__core::default::Default for $name {
             fn default() -> Self {
                 $name
             }
         }
+
+        $crate::__impl_access_ops!{ [] $name }
     )
 }
 
 
 
 #[cfg(test)]
+// masks are written as `1 << (flag_idx + offset)` even if one is 0
+#[allow(clippy::identity_op)]
 mod test {
     use super::*;
 
@@ -808,12 +1339,36 @@ mod test {
 
 
 
+    #[test]
+    fn checks_out_of_range() {
+        assert_eq!(Tab1::try_lookup(1), Some(0b11));
+        assert_eq!(Tab1::try_lookup(4), None);
+        assert_eq!(Tab1::get_checked(1, A12), Some(true));
+        assert_eq!(Tab1::get_checked(2, A12), Some(false));
+        assert_eq!(Tab1::get_checked(4, A12), None);
+        assert!(Tab1::unbound_check_at(0, A11));
+        assert!(!Tab1::unbound_check_at(4, A11));
+        assert!(!Tab1::unbound_check_at(usize::MAX, A11));
+        assert!(Tab1::unbound_check_char('\u{1}', A12));
+        assert!(!Tab1::unbound_check_char('\u{4}', A11));
+        assert!(!Tab1::unbound_check_char('ä', A11));
+    }
+
+    #[test]
+    fn indices_matching_on_merged_table() {
+        assert_eq!(Tab123::indices_matching(A11OrA21).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(Tab123::indices_matching(A11OrA21).ranges().collect::<Vec<_>>(), vec![(0, 2)]);
+        // `!flag` is a mask of the table the flag was defined in
+        assert_eq!(Tab123::indices_matching(A31 & ::Not(A21)).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(Tab123::indices_matching(::Not(A12)).ranges().collect::<Vec<_>>(), vec![(0, 0), (2, 3)]);
+    }
+
     #[test]
     fn merge_tab1_with_tab2() {
         assert_eq!(Tab12::len(), 4);
 
-        assert_eq!(Tab12::mask(A11), 1 << 0 + 1);
-        assert_eq!(Tab12::mask(A12), 1 << 1 + 1);
+        assert_eq!(Tab12::mask(A11), 1 << (0 + 1));
+        assert_eq!(Tab12::mask(A12), 1 << (1 + 1));
         assert_eq!(Tab12::mask(A21), 1 << 0);
 
         assert_eq!(Tab12::lookup(0), 0b0011);
@@ -842,10 +1397,10 @@ mod test {
     fn merge_tab1_with_tab2_with_tab3() {
         assert_eq!(Tab123::len(), 4);
 
-        assert_eq!(Tab123::mask(A11), 1 << 0 + 2);
-        assert_eq!(Tab123::mask(A12), 1 << 1 + 2);
-        assert_eq!(Tab123::mask(A21), 1 << 0 + 1);
-        assert_eq!(Tab123::mask(A31), 1 << 0 + 0);
+        assert_eq!(Tab123::mask(A11), 1 << (0 + 2));
+        assert_eq!(Tab123::mask(A12), 1 << (1 + 2));
+        assert_eq!(Tab123::mask(A21), 1 << (0 + 1));
+        assert_eq!(Tab123::mask(A31), 1 << (0 + 0));
 
         assert_eq!(Tab123::lookup(0), 0b0111);
         assert_eq!(Tab123::lookup(1), 0b1100);
@@ -873,6 +1428,40 @@ mod test {
         assert!(Tab123::check_at(3, A31));
     }
 
+    #[test]
+    fn merged_table_data_is_const() {
+        const MERGED: &[u8] = <Tab123 as Table>::DATA;
+        const CELL: u8 = MERGED[1];
+        assert_eq!(CELL, 0b1100);
+        assert_eq!(MERGED, &[0b0111, 0b1100, 0b0010, 0b0001]);
+    }
+
+    mod merge_of_merged {
+        use super::*;
+
+        new_table! {
+            flags { S1=S1 }
+            struct Short {
+                static data: [u8; 2] = [ -, S1 ];
+            }
+        }
+
+        merge_tables! {
+            struct Tab12S {
+                static data: [u8; 4]
+                    = Tab12 { A11, A12, A21 }
+                    + Short { S1 };
+            }
+        }
+
+        #[test]
+        fn merge_merged_table_with_shorter_table() {
+            assert_eq!(Tab12S::mask(A21), 1 << 1);
+            assert_eq!(Tab12S::mask(S1), 1 << 0);
+            assert_eq!(Tab12S::DATA, &[0b0110, 0b1101, 0b0010, 0b0000]);
+        }
+    }
+
     #[test]
     fn dyn_accessor_any() {
         let acc = Any::new(A11) | A21;
@@ -891,6 +1480,90 @@ mod test {
         assert!(!Tab12::check_at(3, acc));
     }
 
+    #[test]
+    fn check_all_bytes() {
+        assert!(Tab12::check_all(&[0u8, 1, 2][..], A11OrA21));
+        assert!(!Tab12::check_all(&[0u8, 3, 2][..], A11OrA21));
+        assert!(Tab12::check_all(b"", A11OrA21));
+        // out of bounds bytes never match
+        assert!(!Tab12::check_all(&[0u8, 4][..], A11OrA21));
+        assert!(!Tab12::check_all("a", NoFlagsSet));
+    }
+
+    #[test]
+    fn check_all_with_dyn_accessors() {
+        assert!(Tab12::check_all(&[0u8, 1][..], Any::new(A11)));
+        assert!(!Tab12::check_all(&[0u8, 1][..], All::new(A11) & A21));
+        assert!(Tab12::check_all(&[3u8, 3][..], NoFlagsSet));
+    }
+
+    #[test]
+    fn find_first_mismatch_and_match() {
+        let input = [1u8, 0, 3, 2, 9];
+        assert_eq!(Tab12::find_first_mismatch(&input, A11), Some(2));
+        assert_eq!(Tab12::find_first_mismatch(&input, A11OrA21), Some(2));
+        assert_eq!(Tab12::find_first_mismatch(&input[..2], A11), None);
+        assert_eq!(Tab12::find_first_match(&input, A21), Some(1));
+        assert_eq!(Tab12::find_first_match(&input, NoFlagsSet), Some(2));
+        assert_eq!(Tab12::find_first_match(&input[2..], A11), None);
+    }
+
+    #[test]
+    fn prefix_len_and_split() {
+        let input = [0u8, 1, 3, 0];
+        assert_eq!(Tab12::prefix_len(&input, A11), 2);
+        assert_eq!(Tab12::prefix_len(&input[..2], A11), 2);
+        assert_eq!(Tab12::prefix_len(&input, A12), 0);
+
+        let (head, tail) = Tab12::split_at_mismatch(&input, A11);
+        assert_eq!(head, &[0, 1]);
+        assert_eq!(tail, &[3, 0]);
+    }
+
+    new_table! {
+        flags { Lead=LD }
+        struct Tab256 {
+            static data: [u8; 256] = [
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, LD, LD, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, LD, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -
+            ];
+        }
+    }
+
+    #[test]
+    fn split_str_respects_char_boundaries() {
+        // 'ä' is encoded as 0xC3 0xA4, only the lead byte is accepted
+        assert_eq!(Tab256::split_str_at_mismatch("ABäB", Lead), ("AB", "äB"));
+        assert_eq!(Tab256::split_str_at_mismatch("AB", Lead), ("AB", ""));
+        assert_eq!(Tab256::split_str_at_mismatch("cAB", Lead), ("", "cAB"));
+    }
+
+    #[test]
+    fn scanner_of_256_cell_table() {
+        let scanner = Tab256::scanner(Lead);
+        assert_eq!(scanner.class(), ByteClass::EMPTY.with(b'A').with(b'B').with(0xC3));
+        let mut input = b"AB".repeat(20);
+        assert!(scanner.check_all(&input));
+        input[33] = 0xC3;
+        input[35] = 0xA4;
+        assert_eq!(scanner.find_first_mismatch(&input), Tab256::find_first_mismatch(&input, Lead));
+        assert_eq!(Tab256::scanner(!Lead).find_first_match(&input), Some(35));
+    }
+
     mod merge_into_bigger_cell_type {
         new_table! {
             flags { F1=F1, F2=F2, F3=F3, F4=F4, F5=F5 }
@@ -917,16 +1590,143 @@ mod test {
         #[test]
         fn to_larger_merge_was_succesfull() {
             use ::Table;
-            assert_eq!(Table3::mask(F1), 1 << 0 + 5);
-            assert_eq!(Table3::mask(F2), 1 << 1 + 5);
-            assert_eq!(Table3::mask(F3), 1 << 2 + 5);
-            assert_eq!(Table3::mask(F4), 1 << 3 + 5);
-            assert_eq!(Table3::mask(F5), 1 << 4 + 5);
-            assert_eq!(Table3::mask(E1), 1 << 0 + 0);
-            assert_eq!(Table3::mask(E2), 1 << 1 + 0);
-            assert_eq!(Table3::mask(E3), 1 << 2 + 0);
-            assert_eq!(Table3::mask(E4), 1 << 3 + 0);
-            assert_eq!(Table3::mask(E5), 1 << 4 + 0);
+            assert_eq!(Table3::mask(F1), 1 << (0 + 5));
+            assert_eq!(Table3::mask(F2), 1 << (1 + 5));
+            assert_eq!(Table3::mask(F3), 1 << (2 + 5));
+            assert_eq!(Table3::mask(F4), 1 << (3 + 5));
+            assert_eq!(Table3::mask(F5), 1 << (4 + 5));
+            assert_eq!(Table3::mask(E1), 1 << (0 + 0));
+            assert_eq!(Table3::mask(E2), 1 << (1 + 0));
+            assert_eq!(Table3::mask(E3), 1 << (2 + 0));
+            assert_eq!(Table3::mask(E4), 1 << (3 + 0));
+            assert_eq!(Table3::mask(E5), 1 << (4 + 0));
+        }
+    }
+
+    mod merge_into_u128 {
+        new_table! {
+            pub(super) flags {
+                G0=G0, G1=G1, G2=G2, G3=G3, G4=G4, G5=G5, G6=G6, G7=G7,
+                G8=G8, G9=G9, G10=G10, G11=G11, G12=G12, G13=G13, G14=G14, G15=G15,
+                G16=G16, G17=G17, G18=G18, G19=G19, G20=G20, G21=G21, G22=G22, G23=G23,
+                G24=G24, G25=G25, G26=G26, G27=G27, G28=G28, G29=G29, G30=G30, G31=G31,
+                G32=G32, G33=G33, G34=G34, G35=G35, G36=G36, G37=G37, G38=G38, G39=G39,
+                G40=G40, G41=G41, G42=G42, G43=G43, G44=G44, G45=G45, G46=G46, G47=G47,
+                G48=G48, G49=G49, G50=G50, G51=G51, G52=G52, G53=G53, G54=G54, G55=G55,
+                G56=G56, G57=G57, G58=G58, G59=G59, G60=G60, G61=G61, G62=G62, G63=G63
+            }
+            pub(super) struct Table64 {
+                static data: [u64; 3] = [G0|G63, G31|G32, -];
+            }
+        }
+
+        new_table! {
+            pub(super) flags { H1=H1, H2=H2 }
+            pub(super) struct Table2 {
+                static data: [u8; 3] = [H2, -, H1|H2];
+            }
+        }
+
+        merge_tables! {
+            struct Table66 {
+                static data: [u128; 3]
+                    = Table64 { G0, G31, G32, G63 }
+                    + Table2 { H1, H2 };
+            }
+        }
+
+        #[test]
+        fn merge_more_than_64_flags() {
+            use ::Table;
+            use ::ConstFlagCount;
+            assert_eq!(Table66::FLAG_COUNT, 66);
+            assert_eq!(Table66::mask(G0), 1 << (0 + 2));
+            assert_eq!(Table66::mask(G63), 1 << (63 + 2));
+            assert_eq!(Table66::mask(H2), 1 << 1);
+
+            assert_eq!(Table66::lookup(0), 1 << 65 | 1 << 2 | 1 << 1);
+            assert_eq!(Table66::lookup(1), 1 << 34 | 1 << 33);
+            assert_eq!(Table66::lookup(2), 0b11);
+
+            assert!(Table66::check_at(0, G63));
+            assert!(!Table66::check_at(0, G31));
+            assert!(Table66::check_at(1, G32));
+            assert!(Table66::check_at(2, H1));
+        }
+    }
+
+    mod merge_into_bit_set {
+        use super::merge_into_u128::{Table64, Table2, G0, G63, H1, H2};
+
+        new_table! {
+            flags {
+                K0=K0, K1=K1, K2=K2, K3=K3, K4=K4, K5=K5, K6=K6, K7=K7,
+                K8=K8, K9=K9, K10=K10, K11=K11, K12=K12, K13=K13, K14=K14, K15=K15,
+                K16=K16, K17=K17, K18=K18, K19=K19, K20=K20, K21=K21, K22=K22, K23=K23,
+                K24=K24, K25=K25, K26=K26, K27=K27, K28=K28, K29=K29, K30=K30, K31=K31,
+                K32=K32, K33=K33, K34=K34, K35=K35, K36=K36, K37=K37, K38=K38, K39=K39,
+                K40=K40, K41=K41, K42=K42, K43=K43, K44=K44, K45=K45, K46=K46, K47=K47,
+                K48=K48, K49=K49, K50=K50, K51=K51, K52=K52, K53=K53, K54=K54, K55=K55,
+                K56=K56, K57=K57, K58=K58, K59=K59, K60=K60, K61=K61, K62=K62, K63=K63,
+                K64=K64, K65=K65, K66=K66, K67=K67, K68=K68, K69=K69
+            }
+            struct Table70 {
+                static data: [::BitSet<2>; 3] = [K0, K69, K1|K64];
+            }
+        }
+
+        merge_tables! {
+            struct Table136 {
+                static data: [::BitSet<3>; 3]
+                    = Table64 { G0, G63 }
+                    + Table70 { K0, K1, K64, K69 }
+                    + Table2 { H1, H2 };
+            }
+        }
+
+        #[test]
+        fn merge_more_than_128_flags() {
+            use ::{BitSet, Table, ConstFlagCount};
+            assert_eq!(Table70::lookup(1), BitSet::from_words([0, 1 << 5]));
+
+            assert_eq!(Table136::FLAG_COUNT, 136);
+            assert_eq!(Table136::mask(G63), BitSet::from_words([0, 0, 1 << 7]));
+            assert_eq!(Table136::mask(K69), BitSet::from_words([0, 1 << 7, 0]));
+            assert_eq!(Table136::mask(H1), BitSet::from_words([1, 0, 0]));
+
+            assert_eq!(Table136::lookup(0), BitSet::from_words([1 << 2 | 1 << 1, 1 << 8, 1 << 7]));
+            assert_eq!(Table136::lookup(1), BitSet::from_words([0, 1 << 7, 0]));
+            assert_eq!(Table136::lookup(2), BitSet::from_words([1 << 3 | 1 << 1 | 1, 1 << 2, 0]));
+
+            assert!(Table136::check_at(0, G0));
+            assert!(Table136::check_at(1, K69));
+            assert!(Table136::check_at(2, K64));
+            assert!(!Table136::check_at(2, G0));
+        }
+    }
+
+    mod usize_cells {
+        new_table! {
+            flags { U1=U1, U2=U2 }
+            struct Table {
+                static data: [usize; 2] = [U1, U1|U2];
+            }
+        }
+
+        merge_tables! {
+            struct Merged {
+                static data: [usize; 2]
+                    = super::Tab1 { super::A11, super::A12 }
+                    + Table { U1, U2 };
+            }
+        }
+
+        #[test]
+        fn usize_cells_can_be_merged() {
+            use ::Table;
+            assert_eq!(Merged::lookup(0), 0b0101);
+            assert_eq!(Merged::lookup(1), 0b1111);
+            assert!(Merged::check_at(1, U2));
         }
     }
 
@@ -940,21 +1740,105 @@ mod test {
         }
 
 
-        //FIXME: does not compile but should not...
-        // The all indices etc. are found at compiler time, so the compiler does
-        // know that E9 exceeds u8, it just kinda does not check this as there
-        // is nothing like a "compiler-time safe numcast as"...
-        merge_tables! {
-            struct Table2v2 {
-                static data: [u8; 3] = Table2 { E1, E2, E3, E4, E5, E6, E7, E8, E9 };
+        // Both do not compile as Table2 has more flags than an u8 can hold,
+        // this is also the case if not all flags are merged as the bit
+        // positions of the merged flags are kept.
+        //
+        // merge_tables! {
+        //     struct Table2v2 {
+        //         static data: [u8; 3] = Table2 { E1, E2, E3, E4, E5, E6, E7, E8, E9 };
+        //     }
+        // }
+        //
+        // merge_tables! {
+        //     struct Table2v3 {
+        //         static data: [u8; 3] = Table2 { E1, E2 };
+        //     }
+        // }
+
+        new_table! {
+            flags {F1=F1, F2=F2, F3=F3, F4=F4, F5=F5 , F6=F6, F7=F7, F8=F8 }
+            struct Table3 {
+                static data: [u16; 3] = [F1|F2, F6|F7|F8 , -];
             }
         }
 
         merge_tables! {
-            struct Table2v3 {
-                static data: [u8; 3] = Table2 { E1, E2 };
+            struct Table3v2 {
+                static data: [u8; 3] = Table3 { F1, F2, F3, F4, F5, F6, F7, F8 };
             }
         }
+
+        #[test]
+        fn to_smaller_merge_if_flags_fit() {
+            use ::Table;
+            assert_eq!(Table3v2::mask(F1), 1 << 0);
+            assert_eq!(Table3v2::mask(F8), 1 << 7);
+            assert_eq!(Table3v2::DATA, &[0b0000_0011, 0b1110_0000, 0]);
+        }
     }
 
-}
\ No newline at end of file
+    mod sets {
+        use ::{Table, BitSet};
+
+        const fn is_odd(idx: usize) -> bool {
+            idx % 2 == 1
+        }
+
+        new_table! {
+            flags { Digit=DI, Lower=LO, Odd=OD, Dash=DA, Never=NE }
+            struct Chars {
+                static data: [u8; 128] = {
+                    DI = ['0'..='9'],
+                    LO = ['a'..='z'],
+                    OD = fn is_odd,
+                    DA = [b'-', 0x7F, '_'..='_'],
+                    NE = [],
+                };
+            }
+        }
+
+        new_table! {
+            flags { Upper=UP, High=HI }
+            struct Wide {
+                static data: [BitSet<2>; 300] = {
+                    UP = ['A'..='Z'],
+                    HI = [0x80..=0x12B, 'ÿ', 'Ā']
+                };
+            }
+        }
+
+        new_table! {
+            flags { Digit2=DI, Lower2=LO }
+            struct Explicit {
+                static data: [u8; 4] = [ DI, LO, DI|LO, - ];
+            }
+        }
+
+        fn matching<A: ::Access<Chars>>(accessor: A) -> Vec<usize> {
+            Chars::indices_matching(accessor).collect()
+        }
+
+        #[test]
+        fn cells_from_sets() {
+            assert_eq!(matching(Digit), (0x30..0x3A).collect::<Vec<_>>());
+            assert_eq!(matching(Lower), (0x61..0x7B).collect::<Vec<_>>());
+            assert_eq!(matching(Odd), (0..64).map(|idx| idx * 2 + 1).collect::<Vec<_>>());
+            assert_eq!(matching(Dash), vec![0x2D, 0x5F, 0x7F]);
+            assert_eq!(matching(Never), Vec::<usize>::new());
+            assert_eq!(Chars::DATA[b'1' as usize], Chars::mask(Digit) | Chars::mask(Odd));
+        }
+
+        #[test]
+        fn cells_from_sets_beyond_bytes() {
+            assert_eq!(Wide::indices_matching(Upper).ranges().collect::<Vec<_>>(), vec![(0x41, 0x5A)]);
+            assert_eq!(Wide::indices_matching(High).ranges().collect::<Vec<_>>(), vec![(0x80, 0x12B)]);
+        }
+
+        #[test]
+        fn explicit_cells_still_work() {
+            assert_eq!(Explicit::DATA, &[1, 2, 3, 0]);
+        }
+    }
+
+}
@@ -0,0 +1,513 @@
+use core::ops;
+
+use _impl::{Table, TableValue, Access, Any, All, EmptyFlag, NoFlagsSet};
+
+impl<T, A> Access<T> for &A
+    where T: Table, A: Access<T>
+{
+    #[inline(always)]
+    fn check(&self, value: T::Value) -> bool {
+        (**self).check(value)
+    }
+}
+
+/// accessor matching if the wrapped accessor does not match
+///
+/// Normally created through `!accessor`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Not<A>(pub A);
+
+impl<T, A> Access<T> for Not<A>
+    where T: Table, A: Access<T>
+{
+    #[inline(always)]
+    fn check(&self, value: T::Value) -> bool {
+        !self.0.check(value)
+    }
+}
+
+/// accessor matching if both wrapped accessors match
+///
+/// Normally created through `left & right`, see `Masked` for conjunctions
+/// which are checked with a single mask comparison.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct And<A, B>(pub A, pub B);
+
+impl<T, A, B> Access<T> for And<A, B>
+    where T: Table, A: Access<T>, B: Access<T>
+{
+    #[inline(always)]
+    fn check(&self, value: T::Value) -> bool {
+        self.0.check(value) && self.1.check(value)
+    }
+}
+
+/// accessor matching if any of the wrapped accessors match
+///
+/// Normally created through `left | right`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Or<A, B>(pub A, pub B);
+
+impl<T, A, B> Access<T> for Or<A, B>
+    where T: Table, A: Access<T>, B: Access<T>
+{
+    #[inline(always)]
+    fn check(&self, value: T::Value) -> bool {
+        self.0.check(value) || self.1.check(value)
+    }
+}
+
+/// implements `!`, `&` and `|` for an accessor type producing `Not`, `And` and `Or`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_access_ops {
+    ($([$($generics:tt)*] $name:ty),*) => ($(
+        impl<$($generics)*> $crate::__core::ops::Not for $name {
+            type Output = $crate::Not<$name>;
+
+            #[inline(always)]
+            fn not(self) -> Self::Output {
+                $crate::Not(self)
+            }
+        }
+
+        impl<$($generics)* __R> $crate::__core::ops::BitAnd<__R> for $name {
+            type Output = $crate::And<$name, __R>;
+
+            #[inline(always)]
+            fn bitand(self, other: __R) -> Self::Output {
+                $crate::And(self, other)
+            }
+        }
+
+        impl<$($generics)* __R> $crate::__core::ops::BitOr<__R> for $name {
+            type Output = $crate::Or<$name, __R>;
+
+            #[inline(always)]
+            fn bitor(self, other: __R) -> Self::Output {
+                $crate::Or(self, other)
+            }
+        }
+    )*);
+}
+
+__impl_access_ops! {
+    [A, B,] And<A, B>,
+    [A, B,] Or<A, B>,
+    [] EmptyFlag,
+    [] NoFlagsSet
+}
+
+impl<A> ops::Not for Not<A> {
+    type Output = A;
+
+    #[inline(always)]
+    fn not(self) -> A {
+        self.0
+    }
+}
+
+impl<A, R> ops::BitAnd<R> for Not<A> {
+    type Output = And<Not<A>, R>;
+
+    #[inline(always)]
+    fn bitand(self, other: R) -> Self::Output {
+        And(self, other)
+    }
+}
+
+impl<A, R> ops::BitOr<R> for Not<A> {
+    type Output = Or<Not<A>, R>;
+
+    #[inline(always)]
+    fn bitor(self, other: R) -> Self::Output {
+        Or(self, other)
+    }
+}
+
+/// implements `MaskedAnd` for an accessor type producing an `And`
+macro_rules! impl_masked_and {
+    ($([$($generics:tt)*] $name:ty),*) => ($(
+        impl<$($generics)* T: Table> MaskedAnd<T> for $name {
+            type Output = And<Masked<T>, $name>;
+
+            #[inline(always)]
+            fn and_masked(self, masked: Masked<T>) -> Self::Output {
+                And(masked, self)
+            }
+        }
+    )*);
+}
+
+impl_masked_and! {
+    [] Any<T>,
+    [A,] Not<A>,
+    [A, B,] And<A, B>,
+    [A, B,] Or<A, B>,
+    [] EmptyFlag,
+    [] NoFlagsSet
+}
+
+/// accessor matching if all required flags are set and no forbidden flag is set
+///
+/// This is checked with a single mask comparison. Besides through `but_not`,
+/// `with` and `without` it's created by negating a flag or an `Any`, and
+/// conjunctions with it stay a `Masked` as long as the other side only
+/// requires flags:
+///
+/// | expression                      | result                  |
+/// |---------------------------------|-------------------------|
+/// | `!flag`, `!any`                 | `Masked`                |
+/// | `masked & flag`, `masked & all` | `Masked`                |
+/// | `masked & masked`               | `Masked`                |
+/// | `all & masked`                  | `Masked`                |
+/// | `masked & any`                  | `And<Masked, Any>`      |
+/// | `masked \| other`               | `Or<Masked, _>`         |
+/// | `!masked`                       | `Not<Masked>`           |
+///
+/// The negation of a flag is a `Masked` of the table the flag was defined
+/// in, so with merged tables `Not(flag)` has to be used instead. If the
+/// flags are more visible than their table `!flag` is a `Not<flag>`, as the
+/// `Masked` would expose the table.
+///
+/// # Example
+///
+/// ```
+/// #[macro_use]
+/// extern crate lut;
+/// use lut::{Table, All, Masked};
+///
+/// new_table! {
+///     flags { Token=TO, RestrictedToken=RT, Ws=WS }
+///     struct Chars {
+///         static data: [u8; 4] = [ TO, TO|RT, RT, WS ];
+///     }
+/// }
+///
+/// # fn main() {
+/// let acc = All::new(Token).but_not(RestrictedToken);
+/// assert!(Chars::check_at(0, acc));
+/// assert!(!Chars::check_at(1, acc));
+/// assert!(!Chars::check_at(2, acc));
+///
+/// let folded: Masked<Chars> = !RestrictedToken & Token & !Ws;
+/// assert_eq!(folded.required(), Chars::mask(Token));
+/// assert_eq!(folded.forbidden(), Chars::mask(RestrictedToken) | Chars::mask(Ws));
+/// # }
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Masked<T: Table> {
+    required: T::Value,
+    forbidden: T::Value
+}
+
+impl<T> Masked<T>
+    where T: Table
+{
+    #[inline(always)]
+    pub fn empty() -> Self {
+        Masked { required: T::Value::ZERO, forbidden: T::Value::ZERO }
+    }
+
+    #[inline(always)]
+    pub fn from_masks(required: T::Value, forbidden: T::Value) -> Self {
+        Masked { required, forbidden }
+    }
+
+    #[inline(always)]
+    pub fn required(self) -> T::Value {
+        self.required
+    }
+
+    #[inline(always)]
+    pub fn forbidden(self) -> T::Value {
+        self.forbidden
+    }
+
+    /// additionally require all given flags
+    #[inline(always)]
+    pub fn with<I: Into<All<T>>>(mut self, flags: I) -> Self {
+        self.required |= flags.into().mask();
+        self
+    }
+
+    /// additionally forbid all given flags
+    #[inline(always)]
+    pub fn without<I: Into<Any<T>>>(mut self, flags: I) -> Self {
+        self.forbidden |= flags.into().mask();
+        self
+    }
+}
+
+impl<T> Access<T> for Masked<T>
+    where T: Table
+{
+    #[inline(always)]
+    fn check(&self, value: T::Value) -> bool {
+        value & (self.required | self.forbidden) == self.required
+    }
+}
+
+impl<T> From<All<T>> for Masked<T>
+    where T: Table
+{
+    #[inline(always)]
+    fn from(all: All<T>) -> Self {
+        Masked { required: all.mask(), forbidden: T::Value::ZERO }
+    }
+}
+
+/// the right hand side of `masked & self`
+///
+/// Accessors which only require flags, i.e. flags, `All`, `Masked` and the
+/// ones created with `accessor_all!`, are merged into the masks of the
+/// `Masked`. Other accessors are combined with it through an `And`.
+pub trait MaskedAnd<T: Table> {
+    type Output;
+
+    fn and_masked(self, masked: Masked<T>) -> Self::Output;
+}
+
+impl<T> MaskedAnd<T> for Masked<T>
+    where T: Table
+{
+    type Output = Masked<T>;
+
+    #[inline(always)]
+    fn and_masked(self, masked: Masked<T>) -> Masked<T> {
+        Masked {
+            required: masked.required | self.required,
+            forbidden: masked.forbidden | self.forbidden
+        }
+    }
+}
+
+impl<T> MaskedAnd<T> for All<T>
+    where T: Table
+{
+    type Output = Masked<T>;
+
+    #[inline(always)]
+    fn and_masked(self, masked: Masked<T>) -> Masked<T> {
+        masked.with(self)
+    }
+}
+
+impl<T, R> ops::BitAnd<R> for Masked<T>
+    where T: Table, R: MaskedAnd<T>
+{
+    type Output = R::Output;
+
+    #[inline(always)]
+    fn bitand(self, other: R) -> R::Output {
+        other.and_masked(self)
+    }
+}
+
+impl<T, R> ops::BitOr<R> for Masked<T>
+    where T: Table
+{
+    type Output = Or<Masked<T>, R>;
+
+    #[inline(always)]
+    fn bitor(self, other: R) -> Self::Output {
+        Or(self, other)
+    }
+}
+
+impl<T> ops::Not for Masked<T>
+    where T: Table
+{
+    type Output = Not<Masked<T>>;
+
+    /// `!masked` matches if a required flag is missing or a forbidden one is set
+    #[inline(always)]
+    fn not(self) -> Self::Output {
+        Not(self)
+    }
+}
+
+impl<T> All<T>
+    where T: Table
+{
+    /// creates an accessor which requires all flags of this `All` but none of the given flags
+    #[inline(always)]
+    pub fn but_not<I: Into<Any<T>>>(self, flags: I) -> Masked<T> {
+        Masked::from(self).without(flags)
+    }
+}
+
+impl<T> ops::Not for Any<T>
+    where T: Table
+{
+    type Output = Masked<T>;
+
+    /// `!any` matches if none of the flags of `any` are set
+    #[inline(always)]
+    fn not(self) -> Masked<T> {
+        Masked::empty().without(self)
+    }
+}
+
+impl<T, R> ops::BitAnd<R> for Any<T>
+    where T: Table
+{
+    type Output = And<Any<T>, R>;
+
+    #[inline(always)]
+    fn bitand(self, other: R) -> Self::Output {
+        And(self, other)
+    }
+}
+
+impl<T> ops::BitOr<All<T>> for Any<T>
+    where T: Table
+{
+    type Output = Or<Any<T>, All<T>>;
+
+    #[inline(always)]
+    fn bitor(self, other: All<T>) -> Self::Output {
+        Or(self, other)
+    }
+}
+
+impl<T> ops::BitOr<Masked<T>> for Any<T>
+    where T: Table
+{
+    type Output = Or<Any<T>, Masked<T>>;
+
+    #[inline(always)]
+    fn bitor(self, other: Masked<T>) -> Self::Output {
+        Or(self, other)
+    }
+}
+
+impl<T> ops::Not for All<T>
+    where T: Table
+{
+    type Output = Not<All<T>>;
+
+    /// `!all` matches if any flag of `all` is missing, which is no single mask
+    #[inline(always)]
+    fn not(self) -> Self::Output {
+        Not(self)
+    }
+}
+
+impl<T> ops::BitAnd<Masked<T>> for All<T>
+    where T: Table
+{
+    type Output = Masked<T>;
+
+    #[inline(always)]
+    fn bitand(self, other: Masked<T>) -> Masked<T> {
+        other.with(self)
+    }
+}
+
+impl<T> ops::BitAnd<Any<T>> for All<T>
+    where T: Table
+{
+    type Output = And<All<T>, Any<T>>;
+
+    #[inline(always)]
+    fn bitand(self, other: Any<T>) -> Self::Output {
+        And(self, other)
+    }
+}
+
+impl<T, R> ops::BitOr<R> for All<T>
+    where T: Table
+{
+    type Output = Or<All<T>, R>;
+
+    #[inline(always)]
+    fn bitor(self, other: R) -> Self::Output {
+        Or(self, other)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    new_table! {
+        flags { Token=TO, Restricted=RT, Ws=WS }
+        struct Chars {
+            static data: [u8; 5] = [ TO, TO|RT, RT, WS, - ];
+        }
+    }
+
+    accessor_any!{ TokenOrWs = Token | Ws }
+    accessor_all!{ TokenAndRestricted = Token & Restricted }
+
+    fn matching<A: Access<Chars> + Copy>(acc: A) -> Vec<usize> {
+        (0..Chars::len()).filter(|&idx| Chars::check_at(idx, acc)).collect()
+    }
+
+    #[test]
+    fn negated_flag() {
+        assert_eq!(matching(!Token), vec![2, 3, 4]);
+        assert_eq!(matching(!!Token), vec![0, 1]);
+    }
+
+    #[test]
+    fn flag_combinators() {
+        assert_eq!(matching(Token & !Restricted), vec![0]);
+        assert_eq!(matching(Token | Ws), vec![0, 1, 3]);
+        assert_eq!(matching(!Ws & TokenOrWs), vec![0, 1]);
+        assert_eq!(matching(!(Token | Restricted) & !Ws), vec![4]);
+        assert_eq!(matching(!TokenAndRestricted & Restricted), vec![2]);
+        assert_eq!(matching(NoFlagsSet | Ws), vec![3, 4]);
+    }
+
+    #[test]
+    fn dyn_accessor_combinators() {
+        assert_eq!(matching(!Any::new(Token)), vec![2, 3, 4]);
+        assert_eq!(matching(!(Any::new(Token) | Ws)), vec![2, 4]);
+        assert_eq!(matching(Any::new(Token) & Ws), vec![]);
+        assert_eq!(matching(!All::new(Token)), vec![2, 3, 4]);
+        assert_eq!(matching(All::new(Token) | Ws), vec![0, 1, 3]);
+    }
+
+    #[test]
+    fn masked() {
+        let acc = All::new(Token).but_not(Restricted);
+        assert_eq!(acc.required(), Chars::mask(Token));
+        assert_eq!(acc.forbidden(), Chars::mask(Restricted));
+        assert_eq!(matching(acc), vec![0]);
+        assert_eq!(matching(Masked::empty().with(Restricted).without(Any::new(Token) | Ws)), vec![2]);
+        assert_eq!(matching(!Any::new(Ws) & Masked::from(All::new(Restricted))), vec![1, 2]);
+        assert_eq!(matching(Masked::<Chars>::empty()), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn conjunctions_fold_into_masks() {
+        // the annotated types make sure no `And` is involved
+        let acc: Masked<Chars> = !Ws & Token;
+        assert_eq!((acc.required(), acc.forbidden()), (Chars::mask(Token), Chars::mask(Ws)));
+        assert_eq!(matching(acc), vec![0, 1]);
+        let acc: Masked<Chars> = !Any::new(Ws) & All::new(Token) & TokenAndRestricted;
+        assert_eq!(matching(acc), vec![1]);
+        let acc: Masked<Chars> = All::new(Restricted) & !Token;
+        assert_eq!(matching(acc), vec![2]);
+        let acc: Masked<Chars> = !Token & (!Restricted & !Ws);
+        assert_eq!(matching(acc), vec![4]);
+        let acc: And<Masked<Chars>, TokenOrWs> = !Restricted & TokenOrWs;
+        assert_eq!(matching(acc), vec![0, 3]);
+    }
+
+    #[test]
+    fn mask_accessor_operators() {
+        let masked = !Ws & Token;
+        assert_eq!(matching(masked | Ws), vec![0, 1, 3]);
+        assert_eq!(matching(!masked), vec![2, 3, 4]);
+        assert_eq!(matching(masked & Any::new(Restricted)), vec![1]);
+        assert_eq!(matching(Any::new(Ws) | masked), vec![0, 1, 3]);
+        assert_eq!(matching(Any::new(Ws) | All::new(Token)), vec![0, 1, 3]);
+        assert_eq!(matching(All::new(Token) & Any::new(Restricted)), vec![1]);
+        assert_eq!(format!("{:?}", masked), "Masked { required: 1, forbidden: 4 }");
+        assert_eq!(format!("{:?}", Any::<Chars>::new(Ws)), "Any { mask: 4 }");
+    }
+}
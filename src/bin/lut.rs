@@ -0,0 +1,289 @@
+//! command line tool to generate, inspect and verify table descriptions
+//!
+//! The descriptions have the TOML or JSON format read by `lut-build`.
+//! Requires the `cli` feature.
+extern crate lut;
+extern crate lut_build;
+
+use std::ascii;
+use std::env;
+use std::fs;
+use std::process;
+
+use lut::{BitSet, CellBit, DumpFormat, DumpLayout, DynTable, IndexRanges};
+use lut_build::{Builder, TableSpec};
+
+const USAGE: &str = "\
+usage: lut <command> [<args>]
+
+commands:
+    gen <description> [-o <output>]
+        generates the rust code for the description
+    show <description> [<table>] [--format text|markdown|csv] [--columns]
+        renders the cells of all or the given table of the description
+    diff <old description> <new description>
+        lists the changes between two descriptions, exits with 1 if there are any
+    check <description>...
+        validates the descriptions and lists the flag counts of their tables,
+        tables with more flags than `show` can render are rejected
+
+Descriptions are `.toml` or `.json` files as read by `lut-build`.";
+
+/// the most flags a table can have to be rendered by `show`
+///
+/// `show` picks the smallest `BitSet` holding the flags of a table, the
+/// widest one it picks is `BitSet<64>`.
+const MAX_SHOW_FLAG_COUNT: usize = 64 * 64;
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(|arg| &**arg).collect::<Vec<_>>();
+    let code = match run(&args) {
+        Ok(code) => code,
+        Err(Failure::Usage(msg)) => {
+            eprintln!("error: {}\n\n{}", msg, USAGE);
+            2
+        },
+        Err(Failure::Error(msg)) => {
+            eprintln!("{}", msg);
+            1
+        }
+    };
+    process::exit(code);
+}
+
+enum Failure {
+    Usage(String),
+    Error(String),
+}
+
+impl From<lut_build::Error> for Failure {
+    fn from(err: lut_build::Error) -> Self {
+        Failure::Error(err.to_string())
+    }
+}
+
+fn run(args: &[&str]) -> Result<i32, Failure> {
+    match args.split_first() {
+        Some((&"gen", args)) => gen(args),
+        Some((&"show", args)) => show(args),
+        Some((&"diff", args)) => diff(args),
+        Some((&"check", args)) => check(args),
+        Some((&"help", _)) | Some((&"--help", _)) | Some((&"-h", _)) => {
+            println!("{}", USAGE);
+            Ok(0)
+        },
+        Some((cmd, _)) => Err(Failure::Usage(format!("unknown command `{}`", cmd))),
+        None => Err(Failure::Usage("missing command".to_owned()))
+    }
+}
+
+fn gen(args: &[&str]) -> Result<i32, Failure> {
+    let (input, output) = match *args {
+        [input] => (input, None),
+        [input, "-o", output] => (input, Some(output)),
+        _ => return Err(Failure::Usage("`gen` expects a description and optionally `-o <output>`".to_owned()))
+    };
+    let code = Builder::new(input).generate()?;
+    match output {
+        Some(output) => fs::write(output, code)
+            .map_err(|err| Failure::Error(format!("{}: {}", output, err)))?,
+        None => print!("{}", code)
+    }
+    Ok(0)
+}
+
+fn show(args: &[&str]) -> Result<i32, Failure> {
+    let mut input = None;
+    let mut table = None;
+    let mut format = DumpFormat::Text;
+    let mut layout = DumpLayout::FlagNames;
+    let mut args = args.iter();
+    while let Some(&arg) = args.next() {
+        match arg {
+            "--format" => format = match args.next() {
+                Some(&"text") => DumpFormat::Text,
+                Some(&"markdown") => DumpFormat::Markdown,
+                Some(&"csv") => DumpFormat::Csv,
+                _ => return Err(Failure::Usage("`--format` expects text, markdown or csv".to_owned()))
+            },
+            "--columns" => layout = DumpLayout::FlagColumns,
+            _ if input.is_none() => input = Some(arg),
+            _ if table.is_none() => table = Some(arg),
+            _ => return Err(Failure::Usage(format!("unexpected argument `{}`", arg)))
+        }
+    }
+    let input = input.ok_or_else(|| Failure::Usage("`show` expects a description".to_owned()))?;
+
+    let tables = Builder::new(input).tables()?;
+    let selected = tables.iter()
+        .filter(|spec| table.map(|name| spec.name == name).unwrap_or(true))
+        .collect::<Vec<_>>();
+    if selected.is_empty() {
+        let names = tables.iter().map(|spec| &*spec.name).collect::<Vec<_>>();
+        return Err(Failure::Error(format!("{}: no table `{}`, expected one of: {}",
+            input, table.unwrap_or(""), names.join(", "))));
+    }
+    for (idx, spec) in selected.iter().enumerate() {
+        if selected.len() > 1 {
+            if idx > 0 {
+                println!();
+            }
+            println!("{}", spec.name);
+        }
+        print!("{}", render(input, spec, format, layout)?);
+    }
+    Ok(0)
+}
+
+/// renders a table with the smallest `BitSet` holding all of its flags
+fn render(input: &str, spec: &TableSpec, format: DumpFormat, layout: DumpLayout) -> Result<String, Failure> {
+    fn dump<V: CellBit>(spec: &TableSpec, format: DumpFormat, layout: DumpLayout) -> Result<String, Failure> {
+        Ok(dyn_table::<V>(spec)?.dump().format(format).layout(layout).to_string())
+    }
+    match spec.flag_count.div_ceil(64) {
+        0 | 1 => dump::<BitSet<1>>(spec, format, layout),
+        2 => dump::<BitSet<2>>(spec, format, layout),
+        3..=4 => dump::<BitSet<4>>(spec, format, layout),
+        5..=8 => dump::<BitSet<8>>(spec, format, layout),
+        9..=16 => dump::<BitSet<16>>(spec, format, layout),
+        17..=32 => dump::<BitSet<32>>(spec, format, layout),
+        33..=64 => dump::<BitSet<64>>(spec, format, layout),
+        _ => Err(Failure::Error(format!("{}: {}", input, too_many_flags(spec))))
+    }
+}
+
+fn too_many_flags(spec: &TableSpec) -> String {
+    format!("table `{}` has {} flags, but `show` renders at most {}",
+        spec.name, spec.flag_count, MAX_SHOW_FLAG_COUNT)
+}
+
+fn dyn_table<V: CellBit>(spec: &TableSpec) -> Result<DynTable<V>, Failure> {
+    let mut table = DynTable::new(spec.size);
+    for flag_spec in &spec.flags {
+        let flag = table.add_flag(&flag_spec.name)
+            .map_err(|err| Failure::Error(format!("table `{}`: {}", spec.name, err)))?;
+        for &idx in &flag_spec.indices {
+            table.set(idx, flag);
+        }
+    }
+    Ok(table)
+}
+
+fn diff(args: &[&str]) -> Result<i32, Failure> {
+    let (old, new) = match *args {
+        [old, new] => (Builder::new(old).tables()?, Builder::new(new).tables()?),
+        _ => return Err(Failure::Usage("`diff` expects two descriptions".to_owned()))
+    };
+    let changes = changes(&old, &new);
+    for change in &changes {
+        println!("{}", change);
+    }
+    Ok(if changes.is_empty() { 0 } else { 1 })
+}
+
+/// lists the changes between the tables of two descriptions
+fn changes(old: &[TableSpec], new: &[TableSpec]) -> Vec<String> {
+    let mut changes = Vec::new();
+    for old_table in old {
+        let new_table = match new.iter().find(|table| table.name == old_table.name) {
+            Some(table) => table,
+            None => {
+                changes.push(format!("table `{}` was removed", old_table.name));
+                continue;
+            }
+        };
+        let name = &old_table.name;
+        if old_table.cell_type != new_table.cell_type {
+            changes.push(format!("{}: cell type changed from {} to {}",
+                name, old_table.cell_type, new_table.cell_type));
+        }
+        if old_table.size != new_table.size {
+            changes.push(format!("{}: size changed from {} to {}",
+                name, old_table.size, new_table.size));
+        }
+        for old_flag in &old_table.flags {
+            let new_flag = match new_table.flag(&old_flag.name) {
+                Some(flag) => flag,
+                None => {
+                    changes.push(format!("{}: flag `{}` was removed", name, old_flag.name));
+                    continue;
+                }
+            };
+            let added = new_flag.indices.iter().cloned()
+                .filter(|idx| !old_flag.indices.contains(idx));
+            if let Some(ranges) = ranges(added) {
+                changes.push(format!("{}: flag `{}` is now set at {}", name, old_flag.name, ranges));
+            }
+            let removed = old_flag.indices.iter().cloned()
+                .filter(|idx| !new_flag.indices.contains(idx));
+            if let Some(ranges) = ranges(removed) {
+                changes.push(format!("{}: flag `{}` is no longer set at {}",
+                    name, old_flag.name, ranges));
+            }
+        }
+        for new_flag in &new_table.flags {
+            if old_table.flag(&new_flag.name).is_none() {
+                changes.push(format!("{}: flag `{}` was added", name, new_flag.name));
+            }
+        }
+    }
+    for new_table in new {
+        if !old.iter().any(|table| table.name == new_table.name) {
+            changes.push(format!("table `{}` was added", new_table.name));
+        }
+    }
+    changes
+}
+
+/// formats ascending indices as ranges, e.g. `0x30..=0x39 ('0'..='9'), 0x7B ('{')`
+fn ranges<I: Iterator<Item=usize>>(indices: I) -> Option<String> {
+    let ranges = IndexRanges::new(indices).map(|(first, last)| {
+        if first == last {
+            format!("0x{:02X}{}", first, chr(first, None))
+        } else {
+            format!("0x{:02X}..=0x{:02X}{}", first, last, chr(first, Some(last)))
+        }
+    }).collect::<Vec<_>>();
+    if ranges.is_empty() { None } else { Some(ranges.join(", ")) }
+}
+
+/// the escaped chars of a range in parentheses, if they are bytes
+fn chr(first: usize, last: Option<usize>) -> String {
+    let escape = |idx: usize| ascii::escape_default(idx as u8).map(char::from).collect::<String>();
+    match last {
+        _ if first > 255 || last.map(|last| last > 255).unwrap_or(false) => String::new(),
+        Some(last) => format!(" ('{}'..='{}')", escape(first), escape(last)),
+        None => format!(" ('{}')", escape(first))
+    }
+}
+
+fn check(args: &[&str]) -> Result<i32, Failure> {
+    if args.is_empty() {
+        return Err(Failure::Usage("`check` expects at least one description".to_owned()));
+    }
+    let mut code = 0;
+    for input in args {
+        match Builder::new(input).tables() {
+            Ok(tables) => for table in tables {
+                if table.flag_count > MAX_SHOW_FLAG_COUNT {
+                    eprintln!("{}: {}", input, too_many_flags(&table));
+                    code = 1;
+                    continue;
+                }
+                let max = match table.max_flag_count {
+                    Some(max) => format!("{} of {}", table.flag_count, max),
+                    None => table.flag_count.to_string()
+                };
+                let merged = if table.merged { ", merged" } else { "" };
+                println!("{}: table `{}` uses {} flags ({}{})",
+                    input, table.name, max, table.cell_type, merged);
+            },
+            Err(err) => {
+                eprintln!("{}", err);
+                code = 1;
+            }
+        }
+    }
+    Ok(code)
+}
@@ -0,0 +1,212 @@
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not};
+
+use _impl::{CellBit, ConstCell, TableValue};
+
+/// a cell type for tables with more flags than fit into an `u128`
+///
+/// A `BitSet<N>` can hold up to `64 * N` flags, where bit `i` is stored
+/// in bit `i % 64` of the word `i / 64`.
+///
+/// # Example
+///
+/// ```
+/// #[macro_use]
+/// extern crate lut;
+/// use lut::{BitSet, Table};
+///
+/// new_table! {
+///     flags { F1=F1, F2=F2 }
+///     struct Wide {
+///         static data: [BitSet<2>; 2] = [ F1, F1|F2 ];
+///     }
+/// }
+///
+/// # fn main() {
+/// assert!(Wide::check_at(1, F2));
+/// assert_eq!(Wide::lookup(0), BitSet::from_words([0b01, 0]));
+/// # }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BitSet<const N: usize> {
+    words: [u64; N]
+}
+
+impl<const N: usize> BitSet<N> {
+
+    /// creates a bit set from its words, the first word contains the bits 0-63
+    #[inline(always)]
+    pub const fn from_words(words: [u64; N]) -> Self {
+        BitSet { words }
+    }
+
+    /// returns the words of the bit set, the first word contains the bits 0-63
+    #[inline(always)]
+    pub const fn words(self) -> [u64; N] {
+        self.words
+    }
+
+    /// returns true if the given bit is set
+    ///
+    /// # Panics
+    ///
+    /// if `bit` is `64 * N` or larger
+    #[inline(always)]
+    pub const fn is_set(self, bit: usize) -> bool {
+        (self.words[bit / 64] >> (bit % 64)) & 1 == 1
+    }
+
+    /// returns the number of set bits
+    #[inline]
+    pub fn count_ones(self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+}
+
+impl<const N: usize> Default for BitSet<N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<const N: usize> TableValue for BitSet<N> {
+    const MAX_FLAG_COUNT: usize = 64 * N;
+    const ZERO: Self = BitSet { words: [0; N] };
+}
+
+impl<const N: usize> CellBit for BitSet<N> {
+    #[inline]
+    fn bit(idx: usize) -> Self {
+        ConstCell::<Self>::set(Self::ZERO, idx)
+    }
+}
+
+impl<const N: usize> BitAnd for BitSet<N> {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(mut self, other: Self) -> Self {
+        self &= other;
+        self
+    }
+}
+
+impl<const N: usize> BitAndAssign for BitSet<N> {
+    #[inline]
+    fn bitand_assign(&mut self, other: Self) {
+        for (word, other) in self.words.iter_mut().zip(other.words.iter()) {
+            *word &= *other;
+        }
+    }
+}
+
+impl<const N: usize> BitOr for BitSet<N> {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(mut self, other: Self) -> Self {
+        self |= other;
+        self
+    }
+}
+
+impl<const N: usize> BitOrAssign for BitSet<N> {
+    #[inline]
+    fn bitor_assign(&mut self, other: Self) {
+        for (word, other) in self.words.iter_mut().zip(other.words.iter()) {
+            *word |= *other;
+        }
+    }
+}
+
+impl<const N: usize> Not for BitSet<N> {
+    type Output = Self;
+
+    #[inline]
+    fn not(mut self) -> Self {
+        for word in self.words.iter_mut() {
+            *word = !*word;
+        }
+        self
+    }
+}
+
+impl<const N: usize> ConstCell<BitSet<N>> {
+    #[inline(always)]
+    pub const fn is_set(value: BitSet<N>, bit: usize) -> bool {
+        value.is_set(bit)
+    }
+
+    #[inline(always)]
+    pub const fn set(mut value: BitSet<N>, bit: usize) -> BitSet<N> {
+        value.words[bit / 64] |= 1 << (bit % 64);
+        value
+    }
+
+    #[inline(always)]
+    pub const fn or(mut left: BitSet<N>, right: BitSet<N>) -> BitSet<N> {
+        let mut idx = 0;
+        while idx < N {
+            left.words[idx] |= right.words[idx];
+            idx += 1;
+        }
+        left
+    }
+
+    #[inline(always)]
+    pub const fn lowest_bit(value: BitSet<N>) -> usize {
+        let mut idx = 0;
+        while idx < N {
+            if value.words[idx] != 0 {
+                return idx * 64 + value.words[idx].trailing_zeros() as usize;
+            }
+            idx += 1;
+        }
+        N * 64
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use {Any, All, Table, ConstFlagCount};
+
+    new_table! {
+        flags { W1=W1, W2=W2, W3=W3 }
+        struct Wide {
+            static data: [BitSet<1>; 3] = [ W1, W1|W3, - ];
+        }
+    }
+
+    #[test]
+    fn bit_ops() {
+        let a = BitSet::from_words([0b0110, 1 << 63]);
+        let b = BitSet::from_words([0b0011, 1]);
+        assert_eq!(a | b, BitSet::from_words([0b0111, 1 << 63 | 1]));
+        assert_eq!(a & b, BitSet::from_words([0b0010, 0]));
+        assert_eq!((a | b).count_ones(), 5);
+        assert!(a.is_set(64 + 63));
+        assert!(!a.is_set(64));
+    }
+
+    #[test]
+    fn const_cell_ops() {
+        const SET: BitSet<2> = ConstCell::<BitSet<2>>::set(BitSet::ZERO, 70);
+        assert_eq!(SET.words(), [0, 1 << 6]);
+        assert_eq!(ConstCell::<BitSet<2>>::lowest_bit(SET), 70);
+        assert_eq!(ConstCell::<BitSet<2>>::lowest_bit(BitSet::ZERO), 128);
+        assert!(ConstCell::<BitSet<2>>::is_set(SET, 70));
+    }
+
+    #[test]
+    fn table_with_bit_set_cells() {
+        assert_eq!(Wide::FLAG_COUNT, 3);
+        assert_eq!(Wide::mask(W3), BitSet::from_words([0b100]));
+        assert!(Wide::check_at(1, W3));
+        assert!(!Wide::check_at(2, W1));
+        assert!(Wide::check_at(1, Any::new(W2) | W3));
+        assert!(Wide::check_at(1, All::new(W1) & W3));
+        assert!(!Wide::check_at(0, All::new(W1) & W3));
+    }
+}
@@ -0,0 +1,375 @@
+use core::ops;
+
+use _impl::{Table, Access, EmptyFlag};
+
+/// an accessor whose check can be evaluated in a const context
+///
+/// The accessor matches a cell if all (`REQUIRE_ALL`) or any of the
+/// `BITS` are set in it. This is implemented for all flags and for
+/// accessors created with `accessor_any!` and `accessor_all!`, and is what
+/// `byte_class!` uses to compute a `ByteClass` at compile time.
+pub trait ConstAccess<T: Table>: Access<T> {
+    const BITS: &'static [usize];
+    const REQUIRE_ALL: bool;
+}
+
+impl<T> ConstAccess<T> for EmptyFlag
+    where T: Table
+{
+    const BITS: &'static [usize] = &[];
+    const REQUIRE_ALL: bool = false;
+}
+
+/// the set of bytes accepted by an accessor, projected out of a table
+///
+/// This is a packed 256 bit bitmap, so checking a byte needs neither the
+/// table nor the accessor and is a single shift and mask. Indices past the
+/// end of the table are never part of the class, and only the first 256
+/// cells of a table are projected.
+///
+/// Use `byte_class!` to create one at compile time or `ByteClass::project`
+/// for any accessor at runtime.
+///
+/// # Example
+///
+/// ```
+/// #[macro_use]
+/// extern crate lut;
+/// use lut::ByteClass;
+///
+/// new_table! {
+///     flags { Digit=DI, Alpha=AL }
+///     struct Chars {
+///         static data: [u8; 4] = [ DI, DI, AL, - ];
+///     }
+/// }
+///
+/// accessor_any!{ Alnum = Digit | Alpha }
+///
+/// const ALNUM: ByteClass = byte_class!(Chars, Alnum);
+///
+/// # fn main() {
+/// assert_eq!(ALNUM, ByteClass::project::<Chars, _>(Alnum));
+/// assert_eq!(ALNUM.prefix_len(b"\x00\x01\x02\x03"), 3);
+/// assert!(!ALNUM.contains(3));
+/// # }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ByteClass {
+    words: [u64; 4]
+}
+
+impl ByteClass {
+
+    /// the class containing no byte
+    pub const EMPTY: ByteClass = ByteClass { words: [0; 4] };
+
+    /// the class containing all bytes
+    pub const FULL: ByteClass = ByteClass { words: [!0; 4] };
+
+    /// creates a class from its words, bit `i` of word `w` represents byte `64 * w + i`
+    #[inline(always)]
+    pub const fn from_words(words: [u64; 4]) -> Self {
+        ByteClass { words }
+    }
+
+    /// returns the words of the class, bit `i` of word `w` represents byte `64 * w + i`
+    #[inline(always)]
+    pub const fn words(self) -> [u64; 4] {
+        self.words
+    }
+
+    /// projects the bytes accepted by `accessor` out of table `T`
+    pub fn project<T, A>(accessor: A) -> Self
+        where T: Table, A: Access<T>
+    {
+        let len = if T::len() < 256 { T::len() } else { 256 };
+        (0..len)
+            .filter(|&idx| accessor.check(T::lookup(idx)))
+            .fold(ByteClass::EMPTY, |class, idx| class.with(idx as u8))
+    }
+
+    /// returns a copy of this class which also contains `byte`
+    #[inline(always)]
+    pub const fn with(mut self, byte: u8) -> Self {
+        self.words[(byte >> 6) as usize] |= 1 << (byte & 63);
+        self
+    }
+
+    /// returns true if `byte` is part of this class
+    #[inline(always)]
+    pub const fn contains(self, byte: u8) -> bool {
+        (self.words[(byte >> 6) as usize] >> (byte & 63)) & 1 == 1
+    }
+
+    /// returns the class containing all bytes contained in either class
+    #[inline(always)]
+    pub const fn union(self, other: ByteClass) -> Self {
+        let (a, b) = (self.words, other.words);
+        ByteClass { words: [a[0] | b[0], a[1] | b[1], a[2] | b[2], a[3] | b[3]] }
+    }
+
+    /// returns the class containing all bytes contained in both classes
+    #[inline(always)]
+    pub const fn intersection(self, other: ByteClass) -> Self {
+        let (a, b) = (self.words, other.words);
+        ByteClass { words: [a[0] & b[0], a[1] & b[1], a[2] & b[2], a[3] & b[3]] }
+    }
+
+    /// returns the class containing all bytes not contained in this class
+    #[inline(always)]
+    pub const fn complement(self) -> Self {
+        let a = self.words;
+        ByteClass { words: [!a[0], !a[1], !a[2], !a[3]] }
+    }
+
+    /// returns the number of bytes in this class
+    #[inline]
+    pub const fn len(self) -> usize {
+        let a = self.words;
+        (a[0].count_ones() + a[1].count_ones() + a[2].count_ones() + a[3].count_ones()) as usize
+    }
+
+    /// returns true if no byte is part of this class
+    #[inline]
+    pub const fn is_empty(self) -> bool {
+        self.len() == 0
+    }
+
+    /// expands the class into a table with one `bool` per byte
+    pub const fn to_bools(self) -> [bool; 256] {
+        let mut bools = [false; 256];
+        let mut idx = 0;
+        while idx < 256 {
+            bools[idx] = self.contains(idx as u8);
+            idx += 1;
+        }
+        bools
+    }
+
+    /// checks if all bytes of `input` are part of this class
+    #[inline]
+    pub fn check_all<B: AsRef<[u8]> + ?Sized>(self, input: &B) -> bool {
+        input.as_ref().iter().all(|&bch| self.contains(bch))
+    }
+
+    /// returns the index of the first byte in `input` not part of this class
+    #[inline]
+    pub fn find_first_mismatch<B: AsRef<[u8]> + ?Sized>(self, input: &B) -> Option<usize> {
+        input.as_ref().iter().position(|&bch| !self.contains(bch))
+    }
+
+    /// returns the index of the first byte in `input` part of this class
+    #[inline]
+    pub fn find_first_match<B: AsRef<[u8]> + ?Sized>(self, input: &B) -> Option<usize> {
+        input.as_ref().iter().position(|&bch| self.contains(bch))
+    }
+
+    /// returns the length of the longest prefix of `input` in this class
+    #[inline]
+    pub fn prefix_len<B: AsRef<[u8]> + ?Sized>(self, input: &B) -> usize {
+        let input = input.as_ref();
+        self.find_first_mismatch(input).unwrap_or(input.len())
+    }
+
+    /// splits `input` into the longest prefix in this class and the rest
+    #[inline]
+    pub fn split_at_mismatch(self, input: &[u8]) -> (&[u8], &[u8]) {
+        input.split_at(self.prefix_len(input))
+    }
+
+    /// splits `input` into the longest prefix in this class and the rest
+    ///
+    /// If the first rejected byte is part of a multi-byte utf-8 sequence the
+    /// split point is moved back to the start of that sequence.
+    #[inline]
+    pub fn split_str_at_mismatch(self, input: &str) -> (&str, &str) {
+        let mut idx = self.prefix_len(input);
+        while !input.is_char_boundary(idx) {
+            idx -= 1;
+        }
+        input.split_at(idx)
+    }
+}
+
+impl Default for ByteClass {
+    #[inline(always)]
+    fn default() -> Self {
+        ByteClass::EMPTY
+    }
+}
+
+impl ops::BitAnd for ByteClass {
+    type Output = Self;
+
+    #[inline(always)]
+    fn bitand(self, other: Self) -> Self {
+        self.intersection(other)
+    }
+}
+
+impl ops::BitOr for ByteClass {
+    type Output = Self;
+
+    #[inline(always)]
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
+}
+
+impl ops::Not for ByteClass {
+    type Output = Self;
+
+    #[inline(always)]
+    fn not(self) -> Self {
+        self.complement()
+    }
+}
+
+/// projects the bytes accepted by an accessor out of a table at compile time
+///
+/// `byte_class!(Table, Accessor)` evaluates to a `ByteClass` and can be used
+/// to initialize a `const`. The accessor has to implement `ConstAccess`, i.e.
+/// it is a flag or was created with `accessor_any!`/`accessor_all!`. Other
+/// combinations can be built with the `const fn`s of `ByteClass`.
+#[macro_export]
+macro_rules! byte_class {
+    ($table:ty, $accessor:ty) => ({
+        let data = <$table as $crate::Table>::DATA;
+        let bits = <$accessor as $crate::ConstAccess<$table>>::BITS;
+        let require_all = <$accessor as $crate::ConstAccess<$table>>::REQUIRE_ALL;
+        let mut class = $crate::ByteClass::EMPTY;
+        let mut idx = 0;
+        while idx < data.len() && idx < 256 {
+            let mut matches = require_all;
+            let mut bit_idx = 0;
+            while bit_idx < bits.len() {
+                let set = $crate::ConstCell::<<$table as $crate::Table>::Value>::is_set(
+                    data[idx], bits[bit_idx]);
+                matches = if require_all { matches && set } else { matches || set };
+                bit_idx += 1;
+            }
+            if matches {
+                class = class.with(idx as u8);
+            }
+            idx += 1;
+        }
+        class
+    });
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use {Any, BitSet};
+
+    new_table! {
+        flags { Token=TO, Restricted=RT, Ws=WS }
+        struct Chars {
+            static data: [u8; 5] = [ TO, TO|RT, RT, WS, - ];
+        }
+    }
+
+    new_table! {
+        flags { Wide=WI }
+        struct WideChars {
+            static data: [BitSet<1>; 300] = [
+                WI, -, WI, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, WI, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+                -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, WI
+            ];
+        }
+    }
+
+    accessor_any!{ TokenOrWs = Token | Ws }
+    accessor_all!{ TokenAndRestricted = Token & Restricted }
+
+    const TOKEN_OR_WS: ByteClass = byte_class!(Chars, TokenOrWs);
+    const TOKEN_AND_RESTRICTED: ByteClass = byte_class!(Chars, TokenAndRestricted);
+    const RESTRICTED: ByteClass = byte_class!(Chars, Restricted);
+    const WIDE: ByteClass = byte_class!(WideChars, Wide);
+
+    fn members(class: ByteClass) -> Vec<u8> {
+        (0..=255).filter(|&bch| class.contains(bch)).collect()
+    }
+
+    #[test]
+    fn const_projection() {
+        assert_eq!(members(TOKEN_OR_WS), vec![0, 1, 3]);
+        assert_eq!(members(TOKEN_AND_RESTRICTED), vec![1]);
+        assert_eq!(members(RESTRICTED), vec![1, 2]);
+        assert_eq!(members(byte_class!(Chars, EmptyFlag)), vec![]);
+    }
+
+    #[test]
+    fn const_projection_matches_runtime_projection() {
+        assert_eq!(TOKEN_OR_WS, ByteClass::project::<Chars, _>(TokenOrWs));
+        assert_eq!(TOKEN_AND_RESTRICTED, ByteClass::project::<Chars, _>(TokenAndRestricted));
+        assert_eq!(WIDE, ByteClass::project::<WideChars, _>(Wide));
+    }
+
+    #[test]
+    fn only_the_first_256_cells_are_projected() {
+        assert_eq!(members(WIDE), vec![0, 2, 255]);
+    }
+
+    #[test]
+    fn runtime_projection_of_composed_accessors() {
+        let class = ByteClass::project::<Chars, _>(!Token & !(Any::new(Ws) | Restricted));
+        assert_eq!(members(class), vec![4]);
+        assert_eq!(members(class | TOKEN_OR_WS | RESTRICTED), vec![0, 1, 2, 3, 4]);
+        assert_eq!(members(!class & TOKEN_OR_WS), vec![0, 1, 3]);
+        assert_eq!(TOKEN_OR_WS & RESTRICTED, TOKEN_AND_RESTRICTED);
+    }
+
+    #[test]
+    fn set_operations() {
+        assert_eq!(ByteClass::EMPTY.complement(), ByteClass::FULL);
+        assert_eq!(ByteClass::FULL.len(), 256);
+        assert!(ByteClass::EMPTY.is_empty());
+        assert_eq!(ByteClass::EMPTY.with(200).with(3).words(), [1 << 3, 0, 0, 1 << 8]);
+        let bools = TOKEN_OR_WS.to_bools();
+        assert!(bools[0] && bools[1] && !bools[2] && bools[3] && !bools[255]);
+    }
+
+    #[test]
+    fn scanning() {
+        assert!(TOKEN_OR_WS.check_all(b"\x00\x01\x03"));
+        assert!(!TOKEN_OR_WS.check_all(&[0, 2]));
+        assert_eq!(TOKEN_OR_WS.find_first_mismatch(&[1, 3, 4, 0]), Some(2));
+        assert_eq!(TOKEN_OR_WS.find_first_match(&[2, 4, 3]), Some(2));
+        assert_eq!(TOKEN_OR_WS.find_first_match(&[2, 4]), None);
+        assert_eq!(TOKEN_OR_WS.prefix_len(&[0, 1, 3]), 3);
+        assert_eq!(TOKEN_OR_WS.split_at_mismatch(&[0, 2, 1]), (&[0][..], &[2, 1][..]));
+    }
+
+    #[cfg(feature = "media-type-chars")]
+    #[test]
+    fn media_type_chars_projection() {
+        use media_type_chars::{MediaTypeChars, VCharWs};
+        const VCHAR_WS: ByteClass = byte_class!(MediaTypeChars, VCharWs);
+        assert_eq!(VCHAR_WS, ByteClass::project::<MediaTypeChars, _>(VCharWs));
+        assert!(VCHAR_WS.check_all("text/plain; charset=\"utf-8\""));
+        assert_eq!(VCHAR_WS.find_first_mismatch(b"ab\r\n"), Some(2));
+    }
+
+    #[test]
+    fn split_str_respects_char_boundaries() {
+        let class = ByteClass::EMPTY.with(b'a').with(0xC3);
+        assert_eq!(class.split_str_at_mismatch("aaäb"), ("aa", "äb"));
+        assert_eq!(class.split_str_at_mismatch("ab"), ("a", "b"));
+    }
+}
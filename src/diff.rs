@@ -0,0 +1,288 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use _impl::{Table, CellBit, Flag, Access};
+use flags::FlagInfo;
+use dump::index_cell;
+
+/// compares two tables over their common index range
+///
+/// Which flag of the left table corresponds to which flag of the right
+/// table is given through `map` or `map_same_names`, flags which are not
+/// mapped are not compared.
+///
+/// # Example
+///
+/// ```
+/// #[macro_use]
+/// extern crate lut;
+/// use lut::TableDiff;
+///
+/// new_table! {
+///     flags { Token=TO, Ws=WS }
+///     struct HandWritten {
+///         static data: [u8; 3] = [ TO, TO, WS ];
+///     }
+/// }
+///
+/// new_table! {
+///     flags { RfcToken=RT }
+///     struct Generated {
+///         static data: [u8; 3] = [ RT, -, - ];
+///     }
+/// }
+///
+/// # fn main() {
+/// let diff = TableDiff::<HandWritten, Generated>::new().map(Token, RfcToken);
+/// let differences = diff.differences();
+/// assert_eq!(differences.len(), 1);
+/// assert_eq!(differences[0].to_string(), "1 (0x01 '\\x01'): left `Token` is set, right `RfcToken` is not");
+/// # }
+/// ```
+pub struct TableDiff<L: Table, R: Table> {
+    pairs: Vec<(FlagInfo, FlagInfo)>,
+    mark: PhantomData<(L, R)>
+}
+
+/// the differences of two tables at one index, see `TableDiff`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IndexDiff {
+    pub index: usize,
+    pub flags: Vec<FlagDiff>
+}
+
+/// a pair of mapped flags of which only one is set
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FlagDiff {
+    /// the name of the flag in the left table
+    pub left: &'static str,
+    /// the name of the flag in the right table
+    pub right: &'static str,
+    /// true if the left flag is set, false if the right flag is set
+    pub set_in_left: bool
+}
+
+impl<L, R> TableDiff<L, R>
+    where L: Table, R: Table, L::Value: CellBit, R::Value: CellBit
+{
+    /// creates a diff without any mapped flags
+    pub fn new() -> Self {
+        TableDiff { pairs: Vec::new(), mark: PhantomData }
+    }
+
+    /// compares flag `left` of the left table with flag `right` of the right table
+    pub fn map<FL, FR>(mut self, _left: FL, _right: FR) -> Self
+        where FL: Flag<L>, FR: Flag<R>
+    {
+        self.pairs.push((FlagInfo::of::<L, FL>(), FlagInfo::of::<R, FR>()));
+        self
+    }
+
+    /// compares all flags which exist with the same name in both tables
+    pub fn map_same_names(mut self) -> Self {
+        for left in L::FLAGS {
+            if let Some(right) = R::FLAGS.iter().find(|right| right.name == left.name) {
+                self.pairs.push((*left, *right));
+            }
+        }
+        self
+    }
+
+    /// returns all indices at which mapped flags differ
+    pub fn differences(&self) -> Vec<IndexDiff> {
+        let len = L::len().min(R::len());
+        (0..len).filter_map(|index| {
+            let (left, right) = (L::lookup(index), R::lookup(index));
+            let flags = self.pairs.iter().filter_map(|(left_flag, right_flag)| {
+                let set_in_left = is_set(left, left_flag);
+                if set_in_left == is_set(right, right_flag) {
+                    None
+                } else {
+                    Some(FlagDiff { left: left_flag.name, right: right_flag.name, set_in_left })
+                }
+            }).collect::<Vec<_>>();
+
+            if flags.is_empty() { None } else { Some(IndexDiff { index, flags }) }
+        }).collect()
+    }
+
+    /// returns true if the mapped flags are the same over the common index range
+    pub fn is_equivalent(&self) -> bool {
+        self.differences().is_empty()
+    }
+
+    /// panics listing all differences if the tables are not equivalent
+    pub fn assert_equivalent(&self) {
+        let differences = self.differences();
+        if !differences.is_empty() {
+            panic!("tables differ at {} {}:\n{}",
+                differences.len(), indices(differences.len()), lines(&differences));
+        }
+    }
+}
+
+impl<L, R> Default for TableDiff<L, R>
+    where L: Table, R: Table, L::Value: CellBit, R::Value: CellBit
+{
+    fn default() -> Self {
+        TableDiff::new()
+    }
+}
+
+impl fmt::Display for IndexDiff {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        write!(fter, "{}", index_cell(self.index))?;
+        for (idx, flag) in self.flags.iter().enumerate() {
+            let ((set_side, set), (unset_side, unset)) = if flag.set_in_left {
+                (("left", flag.left), ("right", flag.right))
+            } else {
+                (("right", flag.right), ("left", flag.left))
+            };
+            let sep = if idx == 0 { ": " } else { "; " };
+            write!(fter, "{}{} `{}` is set, {} `{}` is not", sep, set_side, set, unset_side, unset)?;
+        }
+        Ok(())
+    }
+}
+
+/// returns all indices of the common index range at which the accessors disagree
+pub fn accessor_differences<L, R, AL, AR>(left: AL, right: AR) -> Vec<usize>
+    where L: Table, R: Table, AL: Access<L>, AR: Access<R>
+{
+    let len = L::len().min(R::len());
+    (0..len)
+        .filter(|&idx| left.check(L::lookup(idx)) != right.check(R::lookup(idx)))
+        .collect()
+}
+
+/// panics listing all differing indices if the accessors disagree
+///
+/// # Example
+///
+/// ```should_panic
+/// #[macro_use]
+/// extern crate lut;
+/// use lut::assert_accessors_equivalent;
+///
+/// new_table! {
+///     flags { Token=TO }
+///     struct HandWritten {
+///         static data: [u8; 2] = [ TO, TO ];
+///     }
+/// }
+///
+/// new_table! {
+///     flags { RfcToken=RT }
+///     struct Generated {
+///         static data: [u8; 2] = [ RT, - ];
+///     }
+/// }
+///
+/// # fn main() {
+/// // panics with "accessors differ at 1 index:\n1 (0x01 '\x01'): only left matches"
+/// assert_accessors_equivalent::<HandWritten, Generated, _, _>(Token, RfcToken);
+/// # }
+/// ```
+pub fn assert_accessors_equivalent<L, R, AL, AR>(left: AL, right: AR)
+    where L: Table, R: Table, AL: Access<L>, AR: Access<R>
+{
+    let differences = accessor_differences::<L, R, _, _>(&left, &right);
+    if !differences.is_empty() {
+        let lines = differences.iter().map(|&idx| {
+            let side = if left.check(L::lookup(idx)) { "left" } else { "right" };
+            format!("{}: only {} matches", index_cell(idx), side)
+        }).collect::<Vec<_>>();
+        panic!("accessors differ at {} {}:\n{}",
+            differences.len(), indices(differences.len()), lines.join("\n"));
+    }
+}
+
+fn is_set<V: CellBit>(value: V, flag: &FlagInfo) -> bool {
+    value & V::bit(flag.bit_index) != V::ZERO
+}
+
+fn indices(count: usize) -> &'static str {
+    if count == 1 { "index" } else { "indices" }
+}
+
+fn lines(differences: &[IndexDiff]) -> String {
+    differences.iter().map(|diff| diff.to_string()).collect::<Vec<_>>().join("\n")
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    mod left {
+        new_table! {
+            pub flags { Token=TO, Ws=WS }
+            pub struct Left {
+                static data: [u8; 5] = [ TO, TO|WS, WS, -, TO ];
+            }
+        }
+    }
+
+    mod right {
+        new_table! {
+            pub flags { Ws=WS, Token=TO, Other=OT }
+            pub struct Right {
+                static data: [u16; 4] = [ TO, TO, WS|TO, OT ];
+            }
+        }
+    }
+
+    use self::left::Left;
+    use self::right::Right;
+
+    #[test]
+    fn differences_by_name() {
+        let diff = TableDiff::<Left, Right>::new().map_same_names();
+        assert_eq!(diff.differences(), vec![
+            IndexDiff { index: 1, flags: vec![
+                FlagDiff { left: "Ws", right: "Ws", set_in_left: true }
+            ]},
+            IndexDiff { index: 2, flags: vec![
+                FlagDiff { left: "Token", right: "Token", set_in_left: false }
+            ]},
+        ]);
+        assert!(!diff.is_equivalent());
+    }
+
+    #[test]
+    fn explicit_mapping() {
+        let diff = TableDiff::<Left, Right>::new().map(left::Token, right::Ws);
+        let lines = diff.differences().iter().map(|diff| diff.to_string()).collect::<Vec<_>>();
+        assert_eq!(lines, vec![
+            "0 (0x00 '\\x00'): left `Token` is set, right `Ws` is not",
+            "1 (0x01 '\\x01'): left `Token` is set, right `Ws` is not",
+            "2 (0x02 '\\x02'): right `Ws` is set, left `Token` is not",
+        ]);
+        TableDiff::<Left, Right>::new().assert_equivalent();
+    }
+
+    #[test]
+    #[should_panic(expected = "tables differ at 2 indices:\n1 (0x01 '\\x01'): left `Ws` is set, right `Ws` is not\n")]
+    fn assert_equivalent_panics() {
+        TableDiff::<Left, Right>::new().map_same_names().assert_equivalent();
+    }
+
+    #[test]
+    #[should_panic(expected = "tables differ at 1 index:\n1 (0x01 '\\x01'): left `Ws` is set, right `Ws` is not")]
+    fn assert_equivalent_panics_at_one_index() {
+        TableDiff::<Left, Right>::new().map(left::Ws, right::Ws).assert_equivalent();
+    }
+
+    #[test]
+    fn accessors() {
+        assert_eq!(accessor_differences::<Left, Right, _, _>(left::Token, right::Token), vec![2]);
+        assert_eq!(accessor_differences::<Left, Right, _, _>(left::Ws, right::Ws), vec![1]);
+        assert_accessors_equivalent::<Left, Right, _, _>(left::Token | left::Ws, !right::Other);
+    }
+
+    #[test]
+    #[should_panic(expected = "accessors differ at 1 index:\n2 (0x02 '\\x02'): only right matches")]
+    fn assert_accessors_equivalent_panics() {
+        assert_accessors_equivalent::<Left, Right, _, _>(left::Token, right::Token);
+    }
+}
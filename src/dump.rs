@@ -0,0 +1,338 @@
+use std::ascii;
+use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
+
+use _impl::{Table, TableValue, CellBit};
+use dyn_table::DynTable;
+
+/// the output format of a `TableDump`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DumpFormat {
+    /// plain text with aligned columns
+    Text,
+    Markdown,
+    Csv
+}
+
+/// the columns of a `TableDump`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DumpLayout {
+    /// one column listing the names of all set flags
+    FlagNames,
+    /// one column per flag marking if it is set with an `x`
+    FlagColumns
+}
+
+/// renders a table as a human readable grid, see `Table::dump`
+///
+/// Every row represents one index and starts with the index, its hex value
+/// and, for indices up to 255, the escaped byte.
+///
+/// # Example
+///
+/// ```
+/// #[macro_use]
+/// extern crate lut;
+/// use lut::{Table, DumpFormat, DumpLayout};
+///
+/// new_table! {
+///     flags { Token=TO, Ws=WS }
+///     struct Chars {
+///         static data: [u8; 3] = [ TO, TO|WS, - ];
+///     }
+/// }
+///
+/// # fn main() {
+/// assert_eq!(Chars::dump().to_string(), "\
+/// idx  hex   char    flags
+/// 0    0x00  '\\x00'  Token
+/// 1    0x01  '\\x01'  Token | Ws
+/// 2    0x02  '\\x02'  -
+/// ");
+///
+/// let csv = Chars::dump().format(DumpFormat::Csv).layout(DumpLayout::FlagColumns);
+/// assert_eq!(csv.to_string(), "\
+/// idx,hex,char,Token,Ws
+/// 0,0x00,'\\x00',x,
+/// 1,0x01,'\\x01',x,x
+/// 2,0x02,'\\x02',,
+/// ");
+/// # }
+/// ```
+pub struct TableDump<T: Table> {
+    format: DumpFormat,
+    layout: DumpLayout,
+    mark: PhantomData<T>
+}
+
+impl<T> TableDump<T>
+    where T: Table
+{
+    /// creates a plain text dump with one column listing the set flags
+    pub fn new() -> Self {
+        TableDump { format: DumpFormat::Text, layout: DumpLayout::FlagNames, mark: PhantomData }
+    }
+
+    pub fn format(mut self, format: DumpFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn layout(mut self, layout: DumpLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+}
+
+impl<T> Default for TableDump<T>
+    where T: Table
+{
+    fn default() -> Self {
+        TableDump::new()
+    }
+}
+
+impl<T> fmt::Display for TableDump<T>
+    where T: Table, T::Value: CellBit
+{
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        let flags = T::FLAGS.iter().map(|flag| flag.name).collect::<Vec<_>>();
+        let set_flags = |idx| T::iter_flags(T::lookup(idx)).map(|flag| flag.name).collect();
+        render(fter, self.format, self.layout, &flags, T::len(), set_flags)
+    }
+}
+
+/// renders a `DynTable` like `TableDump` renders a static table, see `DynTable::dump`
+pub struct DynTableDump<'a, V: TableValue> {
+    table: &'a DynTable<V>,
+    format: DumpFormat,
+    layout: DumpLayout
+}
+
+impl<'a, V> DynTableDump<'a, V>
+    where V: CellBit
+{
+    /// creates a plain text dump with one column listing the set flags
+    pub fn new(table: &'a DynTable<V>) -> Self {
+        DynTableDump { table, format: DumpFormat::Text, layout: DumpLayout::FlagNames }
+    }
+
+    pub fn format(mut self, format: DumpFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn layout(mut self, layout: DumpLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+}
+
+impl<'a, V> fmt::Display for DynTableDump<'a, V>
+    where V: CellBit
+{
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        let table = self.table;
+        let flags = table.flags().collect::<Vec<_>>();
+        let names = flags.iter().map(|&(name, _)| name).collect::<Vec<_>>();
+        let set_flags = |idx| flags.iter()
+            .filter(|&&(_, flag)| table.check_at(idx, flag))
+            .map(|&(name, _)| name)
+            .collect();
+        render(fter, self.format, self.layout, &names, table.len(), set_flags)
+    }
+}
+
+/// renders `len` rows, `set_flags` returns the names of the flags set at an index
+fn render<'a, F>(
+    fter: &mut fmt::Formatter, format: DumpFormat, layout: DumpLayout,
+    flags: &[&str], len: usize, set_flags: F
+) -> fmt::Result
+    where F: Fn(usize) -> Vec<&'a str>
+{
+    let mut header = vec!["idx".to_owned(), "hex".to_owned(), "char".to_owned()];
+    match layout {
+        DumpLayout::FlagNames => header.push("flags".to_owned()),
+        DumpLayout::FlagColumns => header.extend(flags.iter().map(|&name| name.to_owned()))
+    }
+
+    let hex_digits = format!("{:x}", len.saturating_sub(1)).len().max(2);
+    let mut rows = vec![header];
+    rows.extend((0..len).map(|idx| {
+        let mut row = vec![
+            idx.to_string(),
+            format!("0x{:01$X}", idx, hex_digits),
+            char_cell(idx)
+        ];
+        let set = set_flags(idx);
+        match layout {
+            DumpLayout::FlagNames =>
+                row.push(if set.is_empty() { "-".to_owned() } else { set.join(" | ") }),
+            DumpLayout::FlagColumns =>
+                row.extend(flags.iter().map(|name| {
+                    if set.contains(name) { "x".to_owned() } else { String::new() }
+                }))
+        }
+        row
+    }));
+
+    match format {
+        DumpFormat::Text => {
+            let mut widths = vec![0; rows[0].len()];
+            for row in &rows {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell.chars().count());
+                }
+            }
+            for row in &rows {
+                let line = row.iter().zip(&widths)
+                    .map(|(cell, &width)| format!("{:1$}", cell, width))
+                    .collect::<Vec<_>>()
+                    .join("  ");
+                writeln!(fter, "{}", line.trim_end())?;
+            }
+        },
+        DumpFormat::Markdown => {
+            for (idx, row) in rows.iter().enumerate() {
+                for cell in row {
+                    write!(fter, "| {} ", cell.replace('|', "\\|"))?;
+                }
+                writeln!(fter, "|")?;
+                if idx == 0 {
+                    for _ in row {
+                        write!(fter, "|---")?;
+                    }
+                    writeln!(fter, "|")?;
+                }
+            }
+        },
+        DumpFormat::Csv => {
+            for row in &rows {
+                for (col, cell) in row.iter().enumerate() {
+                    if col > 0 {
+                        fter.write_str(",")?;
+                    }
+                    fter.write_str(&csv_cell(cell))?;
+                }
+                writeln!(fter)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// quotes `cell` if it contains a comma, quote or newline
+fn csv_cell(cell: &str) -> Cow<'_, str> {
+    if cell.contains([',', '"', '\n']) {
+        Cow::Owned(format!("\"{}\"", cell.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(cell)
+    }
+}
+
+/// the escaped byte in single quotes for indices up to 255
+pub(crate) fn char_cell(idx: usize) -> String {
+    if idx > 255 {
+        return String::new();
+    }
+    let escaped = ascii::escape_default(idx as u8)
+        .map(char::from)
+        .collect::<String>();
+    format!("'{}'", escaped)
+}
+
+/// the index followed by its hex value and escaped byte, e.g. `34 (0x22 '\"')`
+pub(crate) fn index_cell(idx: usize) -> String {
+    let chr = char_cell(idx);
+    if chr.is_empty() {
+        idx.to_string()
+    } else {
+        format!("{} (0x{:02X} {})", idx, idx, chr)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    new_table! {
+        flags { Token=TO, Ws=WS }
+        struct Chars {
+            static data: [u8; 4] = [ TO, TO|WS, WS, - ];
+        }
+    }
+
+    new_table! {
+        flags { Digit=DI }
+        struct Other {
+            static data: [u8; 4] = [ DI, -, DI, - ];
+        }
+    }
+
+    merge_tables! {
+        struct Merged {
+            static data: [u8; 4] = Chars { Token } + Other { Digit };
+        }
+    }
+
+    #[test]
+    fn char_cells() {
+        assert_eq!(char_cell(b'a' as usize), "'a'");
+        assert_eq!(char_cell(b' ' as usize), "' '");
+        assert_eq!(char_cell(b'\t' as usize), "'\\t'");
+        assert_eq!(char_cell(b'\'' as usize), "'\\''");
+        assert_eq!(char_cell(0xAB), "'\\xab'");
+        assert_eq!(char_cell(256), "");
+    }
+
+    #[test]
+    fn text_flag_columns() {
+        let dump = Merged::dump().layout(DumpLayout::FlagColumns);
+        assert_eq!(dump.to_string(), "\
+idx  hex   char    Digit  Token
+0    0x00  '\\x00'  x      x
+1    0x01  '\\x01'         x
+2    0x02  '\\x02'  x
+3    0x03  '\\x03'
+");
+    }
+
+    #[test]
+    fn markdown() {
+        let dump = Chars::dump().format(DumpFormat::Markdown);
+        assert_eq!(dump.to_string(), "\
+| idx | hex | char | flags |
+|---|---|---|---|
+| 0 | 0x00 | '\\x00' | Token |
+| 1 | 0x01 | '\\x01' | Token \\| Ws |
+| 2 | 0x02 | '\\x02' | Ws |
+| 3 | 0x03 | '\\x03' | - |
+");
+    }
+
+    #[test]
+    fn dyn_table_like_static_table() {
+        let mut table = DynTable::<u8>::new(4);
+        let token = table.add_flag("Token").unwrap();
+        let ws = table.add_flag("Ws").unwrap();
+        table.set(0, token);
+        table.set(1, token);
+        table.set(1, ws);
+        table.set(2, ws);
+        assert_eq!(table.dump().to_string(), Chars::dump().to_string());
+        assert_eq!(table.dump().format(DumpFormat::Csv).layout(DumpLayout::FlagColumns).to_string(),
+            Chars::dump().format(DumpFormat::Csv).layout(DumpLayout::FlagColumns).to_string());
+    }
+
+    #[test]
+    fn csv_escapes_cells() {
+        let dump = Chars::dump().format(DumpFormat::Csv);
+        let mut lines = dump.to_string().lines().map(String::from).collect::<Vec<_>>();
+        lines.truncate(3);
+        assert_eq!(lines, vec!["idx,hex,char,flags", "0,0x00,'\\x00',Token", "1,0x01,'\\x01',Token | Ws"]);
+        assert_eq!(csv_cell(&char_cell(b',' as usize)), "\"','\"");
+        assert_eq!(csv_cell(&char_cell(b'"' as usize)), "\"'\\\"\"'\"");
+    }
+}
@@ -0,0 +1,515 @@
+use std::error::Error;
+use std::fmt;
+use std::ops;
+
+use _impl::{Table, TableValue, CellBit};
+use access::{Not, And, Or};
+use dump::DynTableDump;
+
+/// a lookup table constructed at runtime
+///
+/// Like the tables created by `new_table!` every cell is a set of flags,
+/// but flags are added at runtime and referred to by name. A static table
+/// can be frozen into a `DynTable` with `from_table` and then be edited,
+/// and the flags of static tables can be merged into it.
+///
+/// # Example
+///
+/// ```
+/// use lut::{DynTable, DynAny};
+///
+/// let mut table = DynTable::<u8>::new(128);
+/// let lower = table.add_flag("Lower").unwrap();
+/// let digit = table.add_flag("Digit").unwrap();
+/// table.set_bytes(b"abcdefghijklmnopqrstuvwxyz", lower);
+/// table.set_bytes(b"0123456789", digit);
+///
+/// let ident = DynAny::new(lower) | digit;
+/// assert!(table.check_all("abc123", ident));
+/// assert!(!table.check_all("abc-123", ident));
+/// assert_eq!(table.flag("Digit"), Some(digit));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DynTable<V: TableValue> {
+    data: Box<[V]>,
+    /// the flag names indexed by their bit, bits without flag are `None`
+    flags: Vec<Option<String>>
+}
+
+/// errors returned when adding flags to a `DynTable`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DynTableError {
+    /// a flag with the given name already exists
+    DuplicateFlag(String),
+    /// the cell type can not hold any more flags
+    TooManyFlags { max_flag_count: usize }
+}
+
+impl fmt::Display for DynTableError {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DynTableError::DuplicateFlag(ref name) =>
+                write!(fter, "the table already has a flag named `{}`", name),
+            DynTableError::TooManyFlags { max_flag_count } =>
+                write!(fter, "the cell type can not hold more than {} flags", max_flag_count)
+        }
+    }
+}
+
+impl Error for DynTableError {}
+
+impl<V> DynTable<V>
+    where V: CellBit
+{
+    /// creates a table with `len` empty cells and no flags
+    pub fn new(len: usize) -> Self {
+        DynTable {
+            data: vec![V::ZERO; len].into_boxed_slice(),
+            flags: Vec::new()
+        }
+    }
+
+    /// copies the cells and flags of a static table into a new `DynTable`
+    ///
+    /// The flags keep their bit positions, so the cells are identical.
+    pub fn from_table<T>() -> Self
+        where T: Table<Value=V>
+    {
+        let mut flags = vec![None; T::FLAG_COUNT];
+        for flag in T::FLAGS {
+            flags[flag.bit_index] = Some(flag.name.to_owned());
+        }
+        DynTable { data: T::DATA.into(), flags }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// returns the cells of the table
+    #[inline]
+    pub fn data(&self) -> &[V] {
+        &self.data
+    }
+
+    /// returns the cell at `idx`
+    ///
+    /// # Panics
+    ///
+    /// if `idx` is out of range
+    #[inline]
+    pub fn lookup(&self, idx: usize) -> V {
+        self.data[idx]
+    }
+
+    /// returns the cell at `idx` or `None` if `idx` is out of range
+    #[inline]
+    pub fn try_lookup(&self, idx: usize) -> Option<V> {
+        self.data.get(idx).cloned()
+    }
+
+    /// checks if the cell at `idx` is accepted by `accessor`
+    ///
+    /// # Panics
+    ///
+    /// if `idx` is out of range
+    #[inline]
+    pub fn check_at<A: DynAccess<V>>(&self, idx: usize, accessor: A) -> bool {
+        accessor.check(self.lookup(idx))
+    }
+
+    /// like `check_at` but returns false if `idx` is out of range
+    #[inline]
+    pub fn unbound_check_at<A: DynAccess<V>>(&self, idx: usize, accessor: A) -> bool {
+        self.try_lookup(idx).map(|value| accessor.check(value)).unwrap_or(false)
+    }
+
+    /// checks if all bytes of `input` are accepted by `accessor`
+    ///
+    /// Bytes outside of the index range of the table are never accepted.
+    pub fn check_all<B, A>(&self, input: &B, accessor: A) -> bool
+        where B: AsRef<[u8]> + ?Sized, A: DynAccess<V>
+    {
+        input.as_ref().iter().all(|&bch| self.unbound_check_at(bch as usize, &accessor))
+    }
+
+    /// renders the table as a human readable grid, see `TableDump`
+    #[inline]
+    pub fn dump(&self) -> DynTableDump<'_, V> {
+        DynTableDump::new(self)
+    }
+
+    /// returns the number of bits used by flags
+    ///
+    /// This includes the bits of flags of a merged static table which were
+    /// not merged into it, so it's the bit index the next flag will get.
+    #[inline]
+    pub fn flag_count(&self) -> usize {
+        self.flags.len()
+    }
+
+    /// returns the flag with the given name
+    pub fn flag(&self, name: &str) -> Option<DynFlag<V>> {
+        self.flags.iter()
+            .position(|flag| flag.as_ref().map(|flag| flag == name).unwrap_or(false))
+            .map(DynFlag::from_bit)
+    }
+
+    /// returns the names of all flags together with the flags
+    pub fn flags(&self) -> impl Iterator<Item=(&str, DynFlag<V>)> {
+        self.flags.iter().enumerate().filter_map(|(bit, name)| {
+            name.as_ref().map(|name| (&**name, DynFlag::from_bit(bit)))
+        })
+    }
+
+    /// adds a new flag, which is not set in any cell
+    pub fn add_flag(&mut self, name: &str) -> Result<DynFlag<V>, DynTableError> {
+        self.reserve_flags(1)?;
+        if self.flag(name).is_some() {
+            return Err(DynTableError::DuplicateFlag(name.to_owned()));
+        }
+        self.flags.push(Some(name.to_owned()));
+        Ok(DynFlag::from_bit(self.flags.len() - 1))
+    }
+
+    /// sets `flag` in the cell at `idx`
+    ///
+    /// # Panics
+    ///
+    /// if `idx` is out of range
+    #[inline]
+    pub fn set(&mut self, idx: usize, flag: DynFlag<V>) {
+        self.data[idx] |= flag.mask;
+    }
+
+    /// removes `flag` from the cell at `idx`
+    ///
+    /// # Panics
+    ///
+    /// if `idx` is out of range
+    #[inline]
+    pub fn unset(&mut self, idx: usize, flag: DynFlag<V>) {
+        self.data[idx] &= !flag.mask;
+    }
+
+    /// sets `flag` in the cells of all given bytes
+    ///
+    /// # Panics
+    ///
+    /// if a byte is out of range
+    pub fn set_bytes<B: AsRef<[u8]> + ?Sized>(&mut self, bytes: &B, flag: DynFlag<V>) {
+        for &bch in bytes.as_ref() {
+            self.set(bch as usize, flag);
+        }
+    }
+
+    /// adds all flags of the static table `T` to this table
+    ///
+    /// The flags are added after the existing ones, keeping their relative
+    /// bit positions. If `T` is shorter its missing cells are treated as
+    /// empty, if it is longer its additional cells are ignored.
+    pub fn merge_table<T>(&mut self) -> Result<(), DynTableError>
+        where T: Table, T::Value: CellBit
+    {
+        self.reserve_flags(T::FLAG_COUNT)?;
+        for flag in T::FLAGS {
+            if self.flag(flag.name).is_some() {
+                return Err(DynTableError::DuplicateFlag(flag.name.to_owned()));
+            }
+        }
+
+        let offset = self.flags.len();
+        for (idx, cell) in self.data.iter_mut().enumerate().take(T::len()) {
+            for flag in T::iter_flags(T::lookup(idx)) {
+                *cell |= V::bit(offset + flag.bit_index);
+            }
+        }
+        self.flags.resize(offset + T::FLAG_COUNT, None);
+        for flag in T::FLAGS {
+            self.flags[offset + flag.bit_index] = Some(flag.name.to_owned());
+        }
+        Ok(())
+    }
+
+    fn reserve_flags(&self, count: usize) -> Result<(), DynTableError> {
+        if self.flags.len() + count > V::MAX_FLAG_COUNT {
+            Err(DynTableError::TooManyFlags { max_flag_count: V::MAX_FLAG_COUNT })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// accessor for the cells of a `DynTable`
+pub trait DynAccess<V: TableValue> {
+    fn check(&self, value: V) -> bool;
+}
+
+impl<V, A> DynAccess<V> for &A
+    where V: TableValue, A: DynAccess<V>
+{
+    #[inline(always)]
+    fn check(&self, value: V) -> bool {
+        (**self).check(value)
+    }
+}
+
+impl<V, A> DynAccess<V> for Not<A>
+    where V: TableValue, A: DynAccess<V>
+{
+    #[inline(always)]
+    fn check(&self, value: V) -> bool {
+        !self.0.check(value)
+    }
+}
+
+impl<V, A, B> DynAccess<V> for And<A, B>
+    where V: TableValue, A: DynAccess<V>, B: DynAccess<V>
+{
+    #[inline(always)]
+    fn check(&self, value: V) -> bool {
+        self.0.check(value) && self.1.check(value)
+    }
+}
+
+impl<V, A, B> DynAccess<V> for Or<A, B>
+    where V: TableValue, A: DynAccess<V>, B: DynAccess<V>
+{
+    #[inline(always)]
+    fn check(&self, value: V) -> bool {
+        self.0.check(value) || self.1.check(value)
+    }
+}
+
+/// a flag of a `DynTable`, see `DynTable::flag`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DynFlag<V: TableValue> {
+    bit: usize,
+    mask: V
+}
+
+impl<V> DynFlag<V>
+    where V: CellBit
+{
+    #[inline]
+    fn from_bit(bit: usize) -> Self {
+        DynFlag { bit, mask: V::bit(bit) }
+    }
+
+    /// the index of the bit representing this flag
+    #[inline(always)]
+    pub fn bit_index(self) -> usize {
+        self.bit
+    }
+
+    #[inline(always)]
+    pub fn mask(self) -> V {
+        self.mask
+    }
+}
+
+impl<V> DynAccess<V> for DynFlag<V>
+    where V: TableValue
+{
+    #[inline(always)]
+    fn check(&self, value: V) -> bool {
+        value & self.mask != V::ZERO
+    }
+}
+
+/// accessor matching if any of its flags is set, the `DynTable` version of `Any`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DynAny<V: TableValue> {
+    mask: V
+}
+
+impl<V> DynAny<V>
+    where V: TableValue
+{
+    #[inline(always)]
+    pub fn new(flag: DynFlag<V>) -> Self {
+        DynAny { mask: flag.mask }
+    }
+
+    #[inline(always)]
+    pub fn empty() -> Self {
+        DynAny { mask: V::ZERO }
+    }
+
+    #[inline(always)]
+    pub fn mask(self) -> V {
+        self.mask
+    }
+}
+
+impl<V> ops::BitOr<DynFlag<V>> for DynAny<V>
+    where V: TableValue
+{
+    type Output = Self;
+
+    #[inline(always)]
+    fn bitor(self, flag: DynFlag<V>) -> Self {
+        DynAny { mask: self.mask | flag.mask }
+    }
+}
+
+impl<V> DynAccess<V> for DynAny<V>
+    where V: TableValue
+{
+    #[inline(always)]
+    fn check(&self, value: V) -> bool {
+        value & self.mask != V::ZERO
+    }
+}
+
+/// accessor matching if all of its flags are set, the `DynTable` version of `All`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DynAll<V: TableValue> {
+    mask: V
+}
+
+impl<V> DynAll<V>
+    where V: TableValue
+{
+    #[inline(always)]
+    pub fn new(flag: DynFlag<V>) -> Self {
+        DynAll { mask: flag.mask }
+    }
+
+    #[inline(always)]
+    pub fn empty() -> Self {
+        DynAll { mask: V::ZERO }
+    }
+
+    #[inline(always)]
+    pub fn mask(self) -> V {
+        self.mask
+    }
+}
+
+impl<V> ops::BitAnd<DynFlag<V>> for DynAll<V>
+    where V: TableValue
+{
+    type Output = Self;
+
+    // requiring all flags means joining their masks
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    #[inline(always)]
+    fn bitand(self, flag: DynFlag<V>) -> Self {
+        DynAll { mask: self.mask | flag.mask }
+    }
+}
+
+impl<V> DynAccess<V> for DynAll<V>
+    where V: TableValue
+{
+    #[inline(always)]
+    fn check(&self, value: V) -> bool {
+        value & self.mask == self.mask
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    new_table! {
+        flags { Token=TO, Ws=WS }
+        struct Chars {
+            static data: [u8; 4] = [ TO, TO|WS, WS, - ];
+        }
+    }
+
+    new_table! {
+        flags { Digit=DI, Hex=HX }
+        struct Digits {
+            static data: [u8; 3] = [ DI|HX, HX, DI ];
+        }
+    }
+
+    merge_tables! {
+        struct OnlyHex {
+            static data: [u8; 3] = Digits { Hex };
+        }
+    }
+
+    fn matching<A: DynAccess<u16>>(table: &DynTable<u16>, acc: A) -> Vec<usize> {
+        (0..table.len()).filter(|&idx| table.check_at(idx, &acc)).collect()
+    }
+
+    #[test]
+    fn build_and_check() {
+        let mut table = DynTable::<u16>::new(4);
+        let a = table.add_flag("A").unwrap();
+        let b = table.add_flag("B").unwrap();
+        table.set_bytes(&[0, 1], a);
+        table.set(1, b);
+        table.set(3, b);
+
+        assert_eq!(table.data(), &[0b01, 0b11, 0, 0b10]);
+        assert_eq!(matching(&table, a), vec![0, 1]);
+        assert_eq!(matching(&table, DynAny::new(a) | b), vec![0, 1, 3]);
+        assert_eq!(matching(&table, DynAll::new(a) & b), vec![1]);
+        assert_eq!(matching(&table, And(Not(a), Or(b, DynAny::empty()))), vec![3]);
+        assert_eq!(matching(&table, DynAll::empty()), vec![0, 1, 2, 3]);
+
+        table.unset(1, a);
+        assert_eq!(matching(&table, a), vec![0]);
+        assert!(!table.unbound_check_at(4, DynAll::empty()));
+        assert_eq!(table.try_lookup(4), None);
+    }
+
+    #[test]
+    fn flag_errors() {
+        let mut table = DynTable::<u8>::new(1);
+        table.add_flag("A").unwrap();
+        assert_eq!(table.add_flag("A"), Err(DynTableError::DuplicateFlag("A".to_owned())));
+        for idx in 1..8 {
+            table.add_flag(&idx.to_string()).unwrap();
+        }
+        assert_eq!(table.add_flag("B"), Err(DynTableError::TooManyFlags { max_flag_count: 8 }));
+        assert_eq!(table.flag_count(), 8);
+    }
+
+    #[test]
+    fn freeze_static_table() {
+        let mut table = DynTable::from_table::<Chars>();
+        assert_eq!(table.data(), Chars::DATA);
+        let token = table.flag("Token").unwrap();
+        assert_eq!(token.mask(), Chars::mask(Token));
+        let names = table.flags().map(|(name, _)| name).collect::<Vec<_>>();
+        assert_eq!(names, vec!["Token", "Ws"]);
+
+        table.set(3, token);
+        assert!(table.check_at(3, token));
+        assert!(!Chars::check_at(3, Token));
+    }
+
+    #[test]
+    fn merge_static_tables() {
+        let mut table = DynTable::<u16>::new(4);
+        let custom = table.add_flag("Custom").unwrap();
+        table.set(3, custom);
+        table.merge_table::<Chars>().unwrap();
+        table.merge_table::<OnlyHex>().unwrap();
+
+        // `Digit` is not merged into `OnlyHex`, but still occupies its bit
+        assert_eq!(table.flag_count(), 5);
+        assert_eq!(table.flag("Digit"), None);
+        let hex = table.flag("Hex").unwrap();
+        assert_eq!(hex.bit_index(), 4);
+        assert_eq!(matching(&table, hex), vec![0, 1]);
+        assert_eq!(matching(&table, table.flag("Ws").unwrap()), vec![1, 2]);
+        assert_eq!(matching(&table, custom), vec![3]);
+
+        assert_eq!(table.merge_table::<Chars>(),
+            Err(DynTableError::DuplicateFlag("Token".to_owned())));
+    }
+}
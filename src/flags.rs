@@ -0,0 +1,225 @@
+use core::fmt;
+use core::marker::PhantomData;
+
+use _impl::{Table, TableValue, CellBit, Flag};
+
+/// metadata about a flag of a table, see `Table::FLAGS`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FlagInfo {
+    /// the name of the flag, e.g. `Token`
+    pub name: &'static str,
+    /// the short name used in the table definition, e.g. `TO`
+    pub short_name: &'static str,
+    /// the index of the bit representing the flag
+    pub bit_index: usize,
+    /// the name of the table the flag was defined in, for merged tables
+    /// this is the table it was merged from
+    pub table: &'static str
+}
+
+impl FlagInfo {
+
+    /// returns the metadata of flag `F` in table `T`
+    pub const fn of<T: Table, F: Flag<T>>() -> Self {
+        FlagInfo {
+            name: F::NAME,
+            short_name: F::SHORT_NAME,
+            bit_index: F::BIT_INDEX,
+            table: F::ORIGIN
+        }
+    }
+}
+
+/// a cell value of table `T`, whose `Debug` output lists the set flags
+///
+/// # Example
+///
+/// ```
+/// #[macro_use]
+/// extern crate lut;
+/// use lut::{Table, Flags};
+///
+/// new_table! {
+///     flags { Token=TO, Ws=WS }
+///     struct Chars {
+///         static data: [u8; 2] = [ TO|WS, - ];
+///     }
+/// }
+///
+/// # fn main() {
+/// assert_eq!(format!("{:?}", Chars::flags_at(0)), "Token | Ws");
+/// assert_eq!(format!("{:?}", Chars::flags_at(1)), "-");
+/// # }
+/// ```
+pub struct Flags<T: Table> {
+    value: T::Value,
+    mark: PhantomData<T>
+}
+
+impl<T> Flags<T>
+    where T: Table
+{
+    #[inline(always)]
+    pub fn new(value: T::Value) -> Self {
+        Flags { value, mark: PhantomData }
+    }
+
+    #[inline(always)]
+    pub fn value(&self) -> T::Value {
+        self.value
+    }
+
+    /// iterates over the metadata of all flags set in the value
+    #[inline]
+    pub fn iter(&self) -> FlagIter<T> {
+        FlagIter::new(self.value)
+    }
+}
+
+impl<T> Clone for Flags<T>
+    where T: Table
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Flags<T>
+    where T: Table
+{}
+
+impl<T> PartialEq for Flags<T>
+    where T: Table
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T> Eq for Flags<T>
+    where T: Table
+{}
+
+impl<T> fmt::Debug for Flags<T>
+    where T: Table, T::Value: CellBit
+{
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        let mut flags = self.iter();
+        match flags.next() {
+            None => fter.write_str("-"),
+            Some(first) => {
+                fter.write_str(first.name)?;
+                for flag in flags {
+                    write!(fter, " | {}", flag.name)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// iterator over the flags set in a cell, see `Table::iter_flags`
+pub struct FlagIter<T: Table> {
+    value: T::Value,
+    flags: ::core::slice::Iter<'static, FlagInfo>
+}
+
+impl<T> FlagIter<T>
+    where T: Table
+{
+    #[inline]
+    pub fn new(value: T::Value) -> Self {
+        FlagIter { value, flags: T::FLAGS.iter() }
+    }
+}
+
+impl<T> Clone for FlagIter<T>
+    where T: Table
+{
+    fn clone(&self) -> Self {
+        FlagIter { value: self.value, flags: self.flags.clone() }
+    }
+}
+
+impl<T> Iterator for FlagIter<T>
+    where T: Table, T::Value: CellBit
+{
+    type Item = &'static FlagInfo;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.value;
+        self.flags.by_ref().find(|flag| {
+            value & T::Value::bit(flag.bit_index) != T::Value::ZERO
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    new_table! {
+        flags { Token=TO, Restricted=RT, Ws=WS }
+        struct Chars {
+            static data: [u8; 4] = [ TO, TO|RT, WS, - ];
+        }
+    }
+
+    new_table! {
+        flags { Digit=DI, Hex=HX }
+        struct Digits {
+            static data: [u16; 4] = [ DI|HX, HX, -, DI ];
+        }
+    }
+
+    merge_tables! {
+        struct Merged {
+            static data: [u16; 4] = Chars { Token, Ws } + Digits { Hex };
+        }
+    }
+
+    fn info(name: &'static str, short_name: &'static str, bit_index: usize, table: &'static str)
+        -> FlagInfo
+    {
+        FlagInfo { name, short_name, bit_index, table }
+    }
+
+    #[test]
+    fn flag_metadata() {
+        assert_eq!(Chars::FLAGS, &[
+            info("Token", "TO", 0, "Chars"),
+            info("Restricted", "RT", 1, "Chars"),
+            info("Ws", "WS", 2, "Chars"),
+        ]);
+        assert_eq!(FlagInfo::of::<Chars, Ws>(), Chars::FLAGS[2]);
+    }
+
+    #[test]
+    fn merged_flag_metadata() {
+        // `Restricted` and `Digit` are not merged, but keep their bits reserved
+        assert_eq!(Merged::FLAGS, &[
+            info("Hex", "HX", 1, "Digits"),
+            info("Token", "TO", 2, "Chars"),
+            info("Ws", "WS", 4, "Chars"),
+        ]);
+    }
+
+    #[test]
+    fn iterate_set_flags() {
+        let names = |value| Chars::iter_flags(value).map(|flag| flag.name).collect::<Vec<_>>();
+        assert_eq!(names(Chars::lookup(1)), vec!["Token", "Restricted"]);
+        assert_eq!(names(Chars::lookup(3)), Vec::<&str>::new());
+        let names = Merged::iter_flags(Merged::lookup(0)).map(|flag| flag.name).collect::<Vec<_>>();
+        assert_eq!(names, vec!["Hex", "Token"]);
+    }
+
+    #[test]
+    fn debug_flags() {
+        assert_eq!(format!("{:?}", Chars::flags_at(1)), "Token | Restricted");
+        assert_eq!(format!("{:?}", Chars::flags_at(3)), "-");
+        assert_eq!(format!("{:?}", Merged::flags_at(1)), "Hex | Token");
+        assert_eq!(format!("{:?}", Flags::<Merged>::new(Merged::lookup(2))), "Ws");
+        assert_eq!(Chars::flags_at(0), Flags::new(Chars::mask(Token)));
+    }
+}
@@ -0,0 +1,147 @@
+use core::marker::PhantomData;
+use core::ops::Range;
+
+use _impl::{Table, Access};
+
+/// iterator over the indices of a table whose cells match an accessor
+///
+/// Created by `Table::indices_matching`.
+pub struct IndicesMatching<T: Table, A: Access<T>> {
+    accessor: A,
+    indices: Range<usize>,
+    mark: PhantomData<T>
+}
+
+impl<T, A> IndicesMatching<T, A>
+    where T: Table, A: Access<T>
+{
+    #[inline]
+    pub fn new(accessor: A) -> Self {
+        IndicesMatching { accessor, indices: 0..T::len(), mark: PhantomData }
+    }
+
+    /// groups the matching indices into inclusive ranges, see `IndexRanges`
+    #[inline]
+    pub fn ranges(self) -> IndexRanges<Self> {
+        IndexRanges::new(self)
+    }
+}
+
+impl<T, A> Clone for IndicesMatching<T, A>
+    where T: Table, A: Access<T> + Clone
+{
+    fn clone(&self) -> Self {
+        IndicesMatching {
+            accessor: self.accessor.clone(),
+            indices: self.indices.clone(),
+            mark: PhantomData
+        }
+    }
+}
+
+impl<T, A> Iterator for IndicesMatching<T, A>
+    where T: Table, A: Access<T>
+{
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let accessor = &self.accessor;
+        self.indices.by_ref().find(|&idx| accessor.check(T::lookup(idx)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.indices.size_hint().1)
+    }
+}
+
+/// groups ascending indices into inclusive `(first, last)` ranges
+///
+/// E.g. the indices `1, 2, 3, 7, 9, 10` become `(1, 3), (7, 7), (9, 10)`.
+///
+/// # Example
+///
+/// ```
+/// #[macro_use]
+/// extern crate lut;
+/// use lut::Table;
+///
+/// new_table! {
+///     flags { Token=TO }
+///     struct Chars {
+///         static data: [u8; 6] = [ TO, TO, -, TO, TO, TO ];
+///     }
+/// }
+///
+/// # fn main() {
+/// let ranges = Chars::indices_matching(Token).ranges().collect::<Vec<_>>();
+/// assert_eq!(ranges, vec![(0, 1), (3, 5)]);
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct IndexRanges<I: Iterator<Item=usize>> {
+    indices: I,
+    next: Option<usize>
+}
+
+impl<I> IndexRanges<I>
+    where I: Iterator<Item=usize>
+{
+    /// `indices` has to be strictly ascending
+    #[inline]
+    pub fn new(indices: I) -> Self {
+        IndexRanges { indices, next: None }
+    }
+}
+
+impl<I> Iterator for IndexRanges<I>
+    where I: Iterator<Item=usize>
+{
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        let first = self.next.take().or_else(|| self.indices.next())?;
+        let mut last = first;
+        for idx in self.indices.by_ref() {
+            if idx == last + 1 {
+                last = idx;
+            } else {
+                self.next = Some(idx);
+                break;
+            }
+        }
+        Some((first, last))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    new_table! {
+        flags { Token=TO, Restricted=RT }
+        struct Chars {
+            static data: [u8; 8] = [ TO, TO|RT, RT, TO, TO, -, TO|RT, TO ];
+        }
+    }
+
+    #[test]
+    fn indices() {
+        assert_eq!(Chars::indices_matching(Token).collect::<Vec<_>>(), vec![0, 1, 3, 4, 6, 7]);
+        assert_eq!(Chars::indices_matching(Token & !Restricted).collect::<Vec<_>>(), vec![0, 3, 4, 7]);
+        assert_eq!(Chars::indices_matching(Restricted & !Restricted).next(), None);
+    }
+
+    fn ranges<A: Access<Chars>>(accessor: A) -> Vec<(usize, usize)> {
+        Chars::indices_matching(accessor).ranges().collect()
+    }
+
+    #[test]
+    fn index_ranges() {
+        assert_eq!(ranges(Token), vec![(0, 1), (3, 4), (6, 7)]);
+        assert_eq!(ranges(!Token), vec![(2, 2), (5, 5)]);
+        assert_eq!(IndexRanges::new(vec![3, 4, 5, 9].into_iter()).collect::<Vec<_>>(),
+            vec![(3, 5), (9, 9)]);
+        assert_eq!(IndexRanges::new(0..0).next(), None);
+    }
+}
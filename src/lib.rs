@@ -1,17 +1,92 @@
 //! lut (lookup tables for every one)
 //!
-//! Currently this provides a complex macro. In the future this should
-//! be a proc-macro which will enable most of the benefits behind this
-//! crate with nive ergonomics. Also currently this crate contains some
+//! Currently this provides a complex macro. With the `derive` feature
+//! enabled a proc-macro version of it is available in the `derive` module,
+//! which accepts the same syntax but any visibility and has much better
+//! error messages. Also currently this crate contains some
 //! feature-gated lookup tables. They will be moved out of this crate
 //! in the future.
-#[cfg(test)]
-#[macro_use]
-extern crate lazy_static;
+//!
+//! The crate is `no_std` compatible if the default `std` feature is
+//! disabled, only runtime detection of SIMD instructions needs it.
+//!
+//! The `testing` feature provides `ReferenceCheck`, which checks the flags
+//! of a table against reference predicates in tests.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(any(feature = "std", test))]
+extern crate core;
+#[cfg(feature = "derive")]
+extern crate lut_derive;
+
+// macros refer to `core` through this, as `::core` is not available
+// in 2015 edition crates linking `std`
+#[doc(hidden)]
+pub extern crate core as __core;
 
 #[macro_use]
 mod _impl;
 pub use _impl::*;
 
+#[macro_use]
+mod access;
+pub use access::*;
+
+mod bitset;
+pub use bitset::*;
+
+mod flags;
+pub use flags::*;
+
+mod indices;
+pub use indices::*;
+
+#[macro_use]
+mod byte_class;
+pub use byte_class::*;
+
+mod scan;
+pub use scan::*;
+
+#[cfg(feature = "std")]
+mod dyn_table;
+#[cfg(feature = "std")]
+pub use dyn_table::*;
+
+#[cfg(feature = "std")]
+mod dump;
+#[cfg(feature = "std")]
+pub use dump::*;
+
+#[cfg(feature = "std")]
+mod diff;
+#[cfg(feature = "std")]
+pub use diff::*;
+
+#[cfg(feature = "testing")]
+mod testing;
+#[cfg(feature = "testing")]
+pub use testing::*;
+
 mod tables;
 pub use tables::*;
+
+/// proc-macro versions of `new_table!` and `merge_tables!`
+///
+/// Requires the `derive` feature. The generated code refers to this crate
+/// as `::lut`. If the dependency is renamed, pass its path as first item
+/// of the input:
+///
+/// ```ignore
+/// new_table! {
+///     crate = ::my_lut;
+///     flags { Digit = DI }
+///     struct Chars {
+///         static data: [u8; 2] = [ DI, - ];
+///     }
+/// }
+/// ```
+#[cfg(feature = "derive")]
+pub mod derive {
+    pub use lut_derive::{new_table, merge_tables};
+}
@@ -0,0 +1,462 @@
+use byte_class::ByteClass;
+
+/// a `ByteClass` prepared for vectorised scanning of large inputs
+///
+/// On x86/x86_64 the scanner checks 16 (SSSE3) or 32 (AVX2) bytes at once
+/// using the nibble-split shuffle technique: the low nibble of every byte
+/// selects a bit mask of all high nibbles completing it to a byte of the
+/// class, which is then tested against the bit of its high nibble.
+///
+/// Plain SSE2 has no byte shuffle, so without SSSE3 the class is instead
+/// split into ranges of consecutive bytes and 16 bytes at once are compared
+/// against each range. This is only done for classes made up of at most
+/// `MAX_SSE2_RANGES` ranges, e.g. the token chars of media types.
+///
+/// The instruction set is detected at runtime, or at compile time through
+/// the enabled target features if the `std` feature is disabled. A scalar
+/// fallback is used on other platforms, for classes with too many ranges
+/// on cpus without SSSE3, for short inputs and for the tail of the input.
+///
+/// Preparing the scanner is cheap but not free, so prefer creating it once,
+/// e.g. in a `const` using `byte_class!` or through `Table::scanner`.
+///
+/// # Example
+///
+/// ```
+/// #[macro_use]
+/// extern crate lut;
+/// use lut::ByteScanner;
+///
+/// new_table! {
+///     flags { Lower=LO }
+///     struct Chars {
+///         static data: [u8; 128] = [
+///             -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+///             -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+///             -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+///             -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+///             -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+///             -, -, -, -, -, -, -, -, -, -, -, -, -, -, -, -,
+///             -, LO, LO, LO, LO, LO, LO, LO, LO, LO, LO, LO, LO, LO, LO, LO,
+///             LO, LO, LO, LO, LO, LO, LO, LO, LO, LO, LO, -, -, -, -, -
+///         ];
+///     }
+/// }
+///
+/// const LOWER: ByteScanner = ByteScanner::new(byte_class!(Chars, Lower));
+///
+/// # fn main() {
+/// let input = "abcdefghijklmnopqrstuvwxyzabcdefghijklmnopqrstuvwxyz.";
+/// assert_eq!(LOWER.find_first_mismatch(input), Some(52));
+/// assert!(!LOWER.check_all(input));
+/// # }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ByteScanner {
+    class: ByteClass,
+    /// bit `h` is set in entry `l` if `h << 4 | l` is in the class, for `h < 8`
+    ascii_rows: [u8; 16],
+    /// bit `h - 8` is set in entry `l` if `h << 4 | l` is in the class, for `h >= 8`
+    high_rows: [u8; 16],
+    /// the number of ranges of consecutive bytes the class is made up of
+    range_count: usize,
+    /// the first byte of each of the first `MAX_SSE2_RANGES` ranges
+    range_starts: [u8; MAX_SSE2_RANGES],
+    /// the last byte minus the first byte of each of the first `MAX_SSE2_RANGES` ranges
+    range_lens: [u8; MAX_SSE2_RANGES]
+}
+
+/// the maximal number of byte ranges a class can consist of to be scanned with SSE2
+pub const MAX_SSE2_RANGES: usize = 16;
+
+impl ByteScanner {
+
+    /// prepares the shuffle tables for scanning with `class`
+    pub const fn new(class: ByteClass) -> Self {
+        let mut ascii_rows = [0u8; 16];
+        let mut high_rows = [0u8; 16];
+        let mut bch = 0;
+        while bch < 256 {
+            if class.contains(bch as u8) {
+                let (high, low) = (bch >> 4, bch & 0xF);
+                if high < 8 {
+                    ascii_rows[low] |= 1 << high;
+                } else {
+                    high_rows[low] |= 1 << (high - 8);
+                }
+            }
+            bch += 1;
+        }
+
+        let mut range_count = 0;
+        let mut range_starts = [0u8; MAX_SSE2_RANGES];
+        let mut range_lens = [0u8; MAX_SSE2_RANGES];
+        let mut bch = 0;
+        while bch < 256 {
+            if !class.contains(bch as u8) {
+                bch += 1;
+                continue;
+            }
+            let start = bch;
+            while bch < 256 && class.contains(bch as u8) {
+                bch += 1;
+            }
+            if range_count < MAX_SSE2_RANGES {
+                range_starts[range_count] = start as u8;
+                range_lens[range_count] = (bch - 1 - start) as u8;
+            }
+            range_count += 1;
+        }
+
+        ByteScanner { class, ascii_rows, high_rows, range_count, range_starts, range_lens }
+    }
+
+    /// returns the class this scanner checks for
+    #[inline(always)]
+    pub const fn class(&self) -> ByteClass {
+        self.class
+    }
+
+    /// checks if all bytes of `input` are part of the class
+    #[inline]
+    pub fn check_all<B: AsRef<[u8]> + ?Sized>(&self, input: &B) -> bool {
+        self.find(input.as_ref(), false).is_none()
+    }
+
+    /// returns the index of the first byte in `input` not part of the class
+    #[inline]
+    pub fn find_first_mismatch<B: AsRef<[u8]> + ?Sized>(&self, input: &B) -> Option<usize> {
+        self.find(input.as_ref(), false)
+    }
+
+    /// returns the index of the first byte in `input` part of the class
+    #[inline]
+    pub fn find_first_match<B: AsRef<[u8]> + ?Sized>(&self, input: &B) -> Option<usize> {
+        self.find(input.as_ref(), true)
+    }
+
+    /// returns the length of the longest prefix of `input` in the class
+    #[inline]
+    pub fn prefix_len<B: AsRef<[u8]> + ?Sized>(&self, input: &B) -> usize {
+        let input = input.as_ref();
+        self.find(input, false).unwrap_or(input.len())
+    }
+
+    /// returns the index of the first byte whose membership in the class is `matching`
+    fn find(&self, input: &[u8], matching: bool) -> Option<usize> {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if input.len() >= 32 && x86::has_avx2() {
+                return unsafe { x86::find_avx2(self, input, matching) };
+            }
+            if input.len() >= 16 && x86::has_ssse3() {
+                return unsafe { x86::find_ssse3(self, input, matching) };
+            }
+            if input.len() >= 16 && self.range_count <= MAX_SSE2_RANGES && x86::has_sse2() {
+                return unsafe { x86::find_sse2(self, input, matching) };
+            }
+        }
+        self.find_scalar(input, matching)
+    }
+
+    #[inline]
+    fn find_scalar(&self, input: &[u8], matching: bool) -> Option<usize> {
+        input.iter().position(|&bch| self.class.contains(bch) == matching)
+    }
+}
+
+impl From<ByteClass> for ByteScanner {
+    #[inline]
+    fn from(class: ByteClass) -> Self {
+        ByteScanner::new(class)
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod x86 {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::*;
+
+    use super::ByteScanner;
+
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn has_sse2() -> bool {
+        is_x86_feature_detected!("sse2")
+    }
+
+    #[cfg(not(feature = "std"))]
+    #[inline]
+    pub fn has_sse2() -> bool {
+        cfg!(target_feature = "sse2")
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn has_ssse3() -> bool {
+        is_x86_feature_detected!("ssse3")
+    }
+
+    #[cfg(not(feature = "std"))]
+    #[inline]
+    pub fn has_ssse3() -> bool {
+        cfg!(target_feature = "ssse3")
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn has_avx2() -> bool {
+        is_x86_feature_detected!("avx2")
+    }
+
+    #[cfg(not(feature = "std"))]
+    #[inline]
+    pub fn has_avx2() -> bool {
+        cfg!(target_feature = "avx2")
+    }
+
+    /// `1 << (h & 7)` for every high nibble `h`
+    const HIGH_NIBBLE_BITS: [u8; 16] = [1, 2, 4, 8, 16, 32, 64, 128, 1, 2, 4, 8, 16, 32, 64, 128];
+
+    /// # Safety
+    ///
+    /// The cpu has to support SSE2 and the class of `scanner` may consist
+    /// of at most `MAX_SSE2_RANGES` ranges.
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn find_sse2(scanner: &ByteScanner, input: &[u8], matching: bool) -> Option<usize> {
+        let count = scanner.range_count;
+        let mut starts = [_mm_setzero_si128(); super::MAX_SSE2_RANGES];
+        let mut lens = [_mm_setzero_si128(); super::MAX_SSE2_RANGES];
+        for (idx, (start, len)) in starts.iter_mut().zip(&mut lens).enumerate().take(count) {
+            *start = _mm_set1_epi8(scanner.range_starts[idx] as i8);
+            *len = _mm_set1_epi8(scanner.range_lens[idx] as i8);
+        }
+        let (starts, lens) = (&starts[..count], &lens[..count]);
+        let zero = _mm_setzero_si128();
+
+        let mut offset = 0;
+        while offset + 16 <= input.len() {
+            let chunk = _mm_loadu_si128(input.as_ptr().add(offset) as *const __m128i);
+            let mut inside = zero;
+            for (&start, &len) in starts.iter().zip(lens) {
+                // bytes below the start wrap around to above the length
+                let dist = _mm_sub_epi8(chunk, start);
+                inside = _mm_or_si128(inside, _mm_cmpeq_epi8(_mm_min_epu8(dist, len), dist));
+            }
+            let hits = _mm_movemask_epi8(inside) as u32;
+            let found = if matching { hits } else { !hits & 0xFFFF };
+            if found != 0 {
+                return Some(offset + found.trailing_zeros() as usize);
+            }
+            offset += 16;
+        }
+        scanner.find_scalar(&input[offset..], matching).map(|idx| offset + idx)
+    }
+
+    /// # Safety
+    ///
+    /// The cpu has to support SSSE3.
+    #[target_feature(enable = "ssse3")]
+    pub unsafe fn find_ssse3(scanner: &ByteScanner, input: &[u8], matching: bool) -> Option<usize> {
+        let ascii_rows = _mm_loadu_si128(scanner.ascii_rows.as_ptr() as *const __m128i);
+        let high_rows = _mm_loadu_si128(scanner.high_rows.as_ptr() as *const __m128i);
+        let high_bits = _mm_loadu_si128(HIGH_NIBBLE_BITS.as_ptr() as *const __m128i);
+        let nibble = _mm_set1_epi8(0xF);
+        let zero = _mm_setzero_si128();
+
+        let mut offset = 0;
+        while offset + 16 <= input.len() {
+            let chunk = _mm_loadu_si128(input.as_ptr().add(offset) as *const __m128i);
+            let low = _mm_and_si128(chunk, nibble);
+            let high = _mm_and_si128(_mm_srli_epi16(chunk, 4), nibble);
+            // bytes >= 0x80 are negative, so this selects the rows for them
+            let is_high = _mm_cmplt_epi8(chunk, zero);
+            let rows = _mm_or_si128(
+                _mm_and_si128(is_high, _mm_shuffle_epi8(high_rows, low)),
+                _mm_andnot_si128(is_high, _mm_shuffle_epi8(ascii_rows, low)));
+            let hits = _mm_and_si128(rows, _mm_shuffle_epi8(high_bits, high));
+            let misses = _mm_movemask_epi8(_mm_cmpeq_epi8(hits, zero)) as u32;
+            let found = if matching { !misses & 0xFFFF } else { misses };
+            if found != 0 {
+                return Some(offset + found.trailing_zeros() as usize);
+            }
+            offset += 16;
+        }
+        scanner.find_scalar(&input[offset..], matching).map(|idx| offset + idx)
+    }
+
+    /// # Safety
+    ///
+    /// The cpu has to support AVX2.
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn find_avx2(scanner: &ByteScanner, input: &[u8], matching: bool) -> Option<usize> {
+        // shuffles work on each 128 bit lane separately, so the tables are
+        // duplicated into both lanes
+        let ascii_rows = _mm256_broadcastsi128_si256(
+            _mm_loadu_si128(scanner.ascii_rows.as_ptr() as *const __m128i));
+        let high_rows = _mm256_broadcastsi128_si256(
+            _mm_loadu_si128(scanner.high_rows.as_ptr() as *const __m128i));
+        let high_bits = _mm256_broadcastsi128_si256(
+            _mm_loadu_si128(HIGH_NIBBLE_BITS.as_ptr() as *const __m128i));
+        let nibble = _mm256_set1_epi8(0xF);
+        let zero = _mm256_setzero_si256();
+
+        let mut offset = 0;
+        while offset + 32 <= input.len() {
+            let chunk = _mm256_loadu_si256(input.as_ptr().add(offset) as *const __m256i);
+            let low = _mm256_and_si256(chunk, nibble);
+            let high = _mm256_and_si256(_mm256_srli_epi16(chunk, 4), nibble);
+            // bytes >= 0x80 are negative, so this selects the rows for them
+            let is_high = _mm256_cmpgt_epi8(zero, chunk);
+            let rows = _mm256_or_si256(
+                _mm256_and_si256(is_high, _mm256_shuffle_epi8(high_rows, low)),
+                _mm256_andnot_si256(is_high, _mm256_shuffle_epi8(ascii_rows, low)));
+            let hits = _mm256_and_si256(rows, _mm256_shuffle_epi8(high_bits, high));
+            let misses = _mm256_movemask_epi8(_mm256_cmpeq_epi8(hits, zero)) as u32;
+            let found = if matching { !misses } else { misses };
+            if found != 0 {
+                return Some(offset + found.trailing_zeros() as usize);
+            }
+            offset += 32;
+        }
+        if offset < input.len() && input.len() - offset >= 16 {
+            return find_ssse3(scanner, &input[offset..], matching).map(|idx| offset + idx);
+        }
+        scanner.find_scalar(&input[offset..], matching).map(|idx| offset + idx)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// the class of all bytes in the given inclusive ranges
+    fn ranges(ranges: &[(u8, u8)]) -> ByteClass {
+        ranges.iter()
+            .flat_map(|&(start, end)| start..=end)
+            .fold(ByteClass::EMPTY, |class, bch| class.with(bch))
+    }
+
+    /// classes with members in all high nibble rows, incl. the ones >= 0x80
+    fn classes() -> Vec<ByteClass> {
+        let mut classes = vec![ByteClass::EMPTY, ByteClass::FULL];
+        for class in [ranges(&[(0x7F, 0x80)]), ranges(&[(0x00, 0x00), (0xFF, 0xFF)]), token()] {
+            classes.push(class);
+            classes.push(!class);
+        }
+        for &step in &[1usize, 3, 7, 17, 64, 255] {
+            let class = (0..256).step_by(step)
+                .fold(ByteClass::EMPTY, |class, bch| class.with(bch as u8));
+            classes.push(class);
+            classes.push(!class);
+        }
+        classes
+    }
+
+    /// inputs of many lengths with every byte value at many positions
+    fn inputs() -> Vec<Vec<u8>> {
+        let mut inputs = Vec::new();
+        for len in [0usize, 1, 15, 16, 17, 31, 32, 33, 63, 64, 100].iter().cloned() {
+            for bch in 0..256usize {
+                for &pos in &[0, len / 2, len.saturating_sub(1)] {
+                    let mut input = vec![(bch as u8).wrapping_add(1); len];
+                    if pos < len {
+                        input[pos] = bch as u8;
+                    }
+                    inputs.push(input);
+                }
+            }
+        }
+        inputs
+    }
+
+    /// the token chars of media types, which consist of 9 ranges
+    fn token() -> ByteClass {
+        ranges(&[(b'!', b'!'), (b'#', b'\''), (b'*', b'+'), (b'-', b'.'), (b'0', b'9'),
+            (b'A', b'Z'), (b'^', b'z'), (b'|', b'|'), (b'~', b'~')])
+    }
+
+    fn check_against_scalar<F>(find: F)
+        where F: Fn(&ByteScanner, &[u8], bool) -> Option<usize>
+    {
+        for class in classes() {
+            let scanner = ByteScanner::new(class);
+            for input in inputs() {
+                for &matching in &[true, false] {
+                    assert_eq!(
+                        find(&scanner, &input, matching),
+                        scanner.find_scalar(&input, matching),
+                        "class: {:?}, input: {:?}, matching: {}", class, input, matching);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dispatch_matches_scalar() {
+        check_against_scalar(|scanner, input, matching| scanner.find(input, matching));
+    }
+
+    #[test]
+    fn split_into_ranges() {
+        let range_count = |class| ByteScanner::new(class).range_count;
+        assert_eq!(range_count(ByteClass::EMPTY), 0);
+        assert_eq!(range_count(ByteClass::FULL), 1);
+        assert_eq!(range_count(token()), 9);
+        assert_eq!(range_count(!token()), 10);
+
+        let scanner = ByteScanner::new(ranges(&[(0x00, 0x00), (0x30, 0x39), (0xF0, 0xFF)]));
+        assert_eq!(&scanner.range_starts[..3], &[0x00, 0x30, 0xF0]);
+        assert_eq!(&scanner.range_lens[..3], &[0, 9, 15]);
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[test]
+    fn sse2_matches_scalar() {
+        if x86::has_sse2() {
+            check_against_scalar(|scanner, input, matching| {
+                if scanner.range_count <= MAX_SSE2_RANGES {
+                    unsafe { x86::find_sse2(scanner, input, matching) }
+                } else {
+                    scanner.find_scalar(input, matching)
+                }
+            });
+        }
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[test]
+    fn ssse3_matches_scalar() {
+        if x86::has_ssse3() {
+            check_against_scalar(|scanner, input, matching| unsafe {
+                x86::find_ssse3(scanner, input, matching)
+            });
+        }
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[test]
+    fn avx2_matches_scalar() {
+        if x86::has_avx2() {
+            check_against_scalar(|scanner, input, matching| unsafe {
+                x86::find_avx2(scanner, input, matching)
+            });
+        }
+    }
+
+    #[test]
+    fn scanning_api() {
+        let scanner = ByteScanner::new(ByteClass::EMPTY.with(b'a').with(0xFF));
+        let mut input = vec![b'a'; 40];
+        assert!(scanner.check_all(&input));
+        assert_eq!(scanner.prefix_len(&input), 40);
+        input[33] = 0xFF;
+        input[35] = b'b';
+        assert_eq!(scanner.find_first_mismatch(&input), Some(35));
+        assert_eq!(scanner.prefix_len(&input), 35);
+        assert_eq!(ByteScanner::from(!scanner.class()).find_first_match(&input), Some(35));
+    }
+}
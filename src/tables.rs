@@ -14,6 +14,9 @@
 
 /// a lookup table for parsing/validating Media Types (also know as MIME-Types)
 ///
+/// Also contains `MediaTypeRef`, a parser built on the table, and helpers for
+/// quoting parameter values in `quoting`.
+///
 /// To use lookup the table compile the crate with the `media-type-chars` feature enabled,
 /// else wise this module will be empty.
 pub mod media_type_chars {
@@ -35,11 +38,11 @@ pub mod media_type_chars {
             QText = QC,
             /// The characters mainly needing escaping i.e. '"' and '\\'
             DQuoteOrEscape = DOE,
-            /// Ws  (\t and \r)
+            /// Ws  (\t and space)
             Ws = Ws
         }
 
-        ///
+        /// lookup table for all ascii chars wrt. media type grammar rules
         pub struct MediaTypeChars {
             static data: [u8; 256] = [
                 //0x00 + 0/4/8/C
@@ -106,10 +109,119 @@ pub mod media_type_chars {
     #[cfg(feature = "media-type-chars")]
     accessor_any!{ pub ObsQText = QText | ObsNoWsCtl }
     #[cfg(feature = "media-type-chars")]
-    accessor_any!{ pub ObsQTextWs = QText | ObsNoWsCtl }
+    accessor_any!{ pub ObsQTextWs = QText | ObsNoWsCtl | Ws }
     #[cfg(feature = "media-type-chars")]
     accessor_any!{ pub VChar = QText | DQuoteOrEscape }
     #[cfg(feature = "media-type-chars")]
     accessor_any!{ pub VCharWs = QText | DQuoteOrEscape | Ws }
 
-}
\ No newline at end of file
+    #[cfg(feature = "media-type-chars")]
+    pub mod quoting;
+
+    #[cfg(feature = "media-type-chars")]
+    mod media_type;
+    #[cfg(feature = "media-type-chars")]
+    pub use self::media_type::*;
+
+    #[cfg(all(test, feature = "media-type-chars"))]
+    mod test {
+        use super::*;
+        use {Access, Table};
+
+        #[cfg(feature = "testing")]
+        const TSPECIALS: &[u8] = b"()<>@,;:\\\"/[]?=";
+
+        fn is_vchar(b: u8) -> bool {
+            (0x21..=0x7E).contains(&b)
+        }
+
+        // RFC 5322 obs-NO-WS-CTL
+        fn is_obs_no_ws_ctl(b: u8) -> bool {
+            matches!(b, 0x01..=0x08 | 0x0B | 0x0C | 0x0E..=0x1F | 0x7F)
+        }
+
+        // RFC 5322 qtext
+        fn is_qtext(b: u8) -> bool {
+            is_vchar(b) && b != b'"' && b != b'\\'
+        }
+
+        fn is_ws(b: u8) -> bool {
+            b == b'\t' || b == b' '
+        }
+
+        #[cfg(feature = "testing")]
+        #[test]
+        fn flags_match_rfc_grammar() {
+            use testing::ReferenceCheck;
+
+            ReferenceCheck::<MediaTypeChars>::new()
+                // RFC 5322 ctext
+                .flag(CText, |b| is_vchar(b) && !b"()\\".contains(&b))
+                // RFC 5322 obs-NO-WS-CTL
+                .flag(ObsNoWsCtl, is_obs_no_ws_ctl)
+                // RFC 7230 tchar
+                .flag(HttpToken, |b| {
+                    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+                })
+                // RFC 2045 token
+                .flag(Token, |b| is_vchar(b) && !TSPECIALS.contains(&b))
+                // RFC 6838 restricted-name-chars
+                .flag(RestrictedToken, |b| {
+                    b.is_ascii_alphanumeric() || b"!#$&-^_.+".contains(&b)
+                })
+                .flag(QText, is_qtext)
+                .flag(DQuoteOrEscape, |b| b == b'"' || b == b'\\')
+                .flag(Ws, is_ws)
+                .assert_matches();
+        }
+
+        fn assert_accessor<A: Access<MediaTypeChars> + Copy>(name: &str, accessor: A, expected: fn(u8) -> bool) {
+            for idx in 0..MediaTypeChars::len() {
+                assert_eq!(MediaTypeChars::check_at(idx, accessor), expected(idx as u8),
+                    "{} at 0x{:02X}", name, idx);
+            }
+        }
+
+        #[test]
+        fn accessors_match_rfc_grammar() {
+            assert_accessor("QTextWs", QTextWs, |b| is_qtext(b) || is_ws(b));
+            // RFC 5322 obs-qtext
+            assert_accessor("ObsQText", ObsQText, |b| is_qtext(b) || is_obs_no_ws_ctl(b));
+            assert_accessor("ObsQTextWs", ObsQTextWs, |b| is_qtext(b) || is_obs_no_ws_ctl(b) || is_ws(b));
+            assert_accessor("VChar", VChar, is_vchar);
+            assert_accessor("VCharWs", VCharWs, |b| is_vchar(b) || is_ws(b));
+        }
+
+        #[test]
+        fn scanner_matches_table() {
+            let mut body = b"multipart/form-data+x.y".repeat(100);
+            assert!(MediaTypeChars::scanner(VCharWs).check_all(&body));
+            body[1000] = b'\x7F';
+            body[2000] = b' ';
+            assert_eq!(MediaTypeChars::scanner(VCharWs).find_first_mismatch(&body), Some(1000));
+            // `/` is a tspecial
+            assert_eq!(MediaTypeChars::scanner(Token).find_first_mismatch(&body), Some(9));
+            assert_eq!(MediaTypeChars::scanner(RestrictedToken).find_first_mismatch(&body),
+                MediaTypeChars::find_first_mismatch(&body, RestrictedToken));
+        }
+    }
+}
+
+/// a lookup table for parsing/validating URIs (RFC 3986) and percent-encoding helpers
+///
+/// To use the table compile the crate with the `uri-chars` feature enabled,
+/// else wise this module will be empty.
+pub mod uri_chars;
+
+/// a lookup table for HTTP/1.1 header fields (RFC 9110/9112) and cookies (RFC 6265)
+///
+/// To use the table compile the crate with the `http-chars` feature enabled,
+/// else wise this module will be empty.
+pub mod http_chars;
+
+/// a lookup table for mail headers (RFC 5322) including the internationalized
+/// variants of RFC 6532 and validators for local-parts
+///
+/// To use the table compile the crate with the `mail-chars` feature enabled,
+/// else wise this module will be empty.
+pub mod mail_chars;
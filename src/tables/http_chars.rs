@@ -0,0 +1,176 @@
+#[cfg(feature = "http-chars")]
+use _impl::Table;
+
+#[cfg(feature = "http-chars")]
+new_table! {
+
+    pub flags {
+        /// tchar: "!" / "#" / "$" / "%" / "&" / "'" / "*" / "+" / "-" / "." /
+        /// "^" / "_" / "`" / "|" / "~" / DIGIT / ALPHA
+        TChar = TC,
+        /// field-vchar: VCHAR / obs-text
+        FieldVChar = FV,
+        /// obs-text: %x80-FF
+        ObsText = OT,
+        /// qdtext: HTAB / SP / %x21 / %x23-5B / %x5D-7E / obs-text
+        QdText = QD,
+        /// the char after the '\\' of a quoted-pair: HTAB / SP / VCHAR / obs-text
+        QuotedPair = QP,
+        /// ctext: HTAB / SP / %x21-27 / %x2A-5B / %x5D-7E / obs-text
+        CText = CT,
+        /// OWS: SP / HTAB
+        Ows = WS,
+        /// cookie-octet (RFC 6265): %x21 / %x23-2B / %x2D-3A / %x3C-5B / %x5D-7E
+        CookieOctet = CO
+    }
+
+    /// lookup table for all bytes wrt. HTTP/1.1 header field grammar rules
+    pub struct HttpChars {
+        static data: [u8; 256] = {
+            TC = ['!', '#'..='\'', '*', '+', '-', '.', '0'..='9', 'A'..='Z', '^'..='z', '|', '~'],
+            FV = [0x21..=0x7E, 0x80..=0xFF],
+            OT = [0x80..=0xFF],
+            QD = ['\t', ' ', '!', '#'..='[', ']'..='~', 0x80..=0xFF],
+            QP = ['\t', ' '..='~', 0x80..=0xFF],
+            CT = ['\t', ' ', '!'..='\'', '*'..='[', ']'..='~', 0x80..=0xFF],
+            WS = ['\t', ' '],
+            CO = ['!', '#'..='+', '-'..=':', '<'..='[', ']'..='~']
+        };
+    }
+}
+
+#[cfg(feature = "http-chars")]
+accessor_any!{
+    /// the bytes of a field-value: field-vchar / SP / HTAB
+    pub FieldValueChar = FieldVChar | Ows
+}
+#[cfg(feature = "http-chars")]
+accessor_any!{
+    /// the bytes of a quoted-string between its DQUOTEs, without the '"' and
+    /// '\\' which need a quoted-pair
+    pub QuotedStringChar = QdText
+}
+
+/// true if `input` is a valid field-name, i.e. a token
+#[cfg(feature = "http-chars")]
+pub fn is_header_name<B: AsRef<[u8]> + ?Sized>(input: &B) -> bool {
+    let input = input.as_ref();
+    !input.is_empty() && HttpChars::check_all(input, TChar)
+}
+
+/// true if `input` is a valid field-value
+///
+/// The value has to be trimmed, as OWS around it is not part of it.
+/// Obsolete line folding is not accepted.
+#[cfg(feature = "http-chars")]
+pub fn is_header_value<B: AsRef<[u8]> + ?Sized>(input: &B) -> bool {
+    let input = input.as_ref();
+    let is_ows = |byte: Option<&u8>| byte
+        .map(|&byte| HttpChars::check_at(byte as usize, Ows))
+        .unwrap_or(false);
+    !is_ows(input.first())
+        && !is_ows(input.last())
+        && HttpChars::check_all(input, FieldValueChar)
+}
+
+/// true if `input` is a valid cookie-name (RFC 6265), i.e. a token
+#[cfg(feature = "http-chars")]
+pub fn is_cookie_name<B: AsRef<[u8]> + ?Sized>(input: &B) -> bool {
+    is_header_name(input)
+}
+
+/// true if `input` is a valid cookie-value (RFC 6265)
+///
+/// The value consists of cookie-octets, optionally enclosed in DQUOTEs.
+#[cfg(feature = "http-chars")]
+pub fn is_cookie_value<B: AsRef<[u8]> + ?Sized>(input: &B) -> bool {
+    let input = input.as_ref();
+    let octets = match input {
+        [b'"', octets @ .., b'"'] => octets,
+        _ => input
+    };
+    HttpChars::check_all(octets, CookieOctet)
+}
+
+#[cfg(all(test, feature = "http-chars"))]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn flags_match_rfc_grammar() {
+        use testing::ReferenceCheck;
+
+        fn is_vchar(b: u8) -> bool {
+            (0x21..=0x7E).contains(&b)
+        }
+        fn is_obs_text(b: u8) -> bool {
+            b >= 0x80
+        }
+        fn is_ws(b: u8) -> bool {
+            b == b'\t' || b == b' '
+        }
+
+        ReferenceCheck::<HttpChars>::new()
+            .flag(TChar, |b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+            .flag(FieldVChar, |b| is_vchar(b) || is_obs_text(b))
+            .flag(ObsText, is_obs_text)
+            .flag(QdText, |b| is_ws(b) || (is_vchar(b) && b != b'"' && b != b'\\') || is_obs_text(b))
+            .flag(QuotedPair, |b| is_ws(b) || is_vchar(b) || is_obs_text(b))
+            .flag(CText, |b| is_ws(b) || (is_vchar(b) && !b"()\\".contains(&b)) || is_obs_text(b))
+            .flag(Ows, is_ws)
+            .flag(CookieOctet, |b| is_vchar(b) && !b"\",;\\".contains(&b))
+            .assert_matches();
+    }
+
+    #[test]
+    fn header_names() {
+        assert!(is_header_name("Content-Type"));
+        assert!(is_header_name("x-!#$%&'*+.^_`|~"));
+        assert!(!is_header_name(""));
+        assert!(!is_header_name("Content Type"));
+        assert!(!is_header_name("Content-Type:"));
+        assert!(!is_header_name("Ä"));
+    }
+
+    #[test]
+    fn header_values() {
+        assert!(is_header_value(""));
+        assert!(is_header_value("text/plain; charset=\"utf-8\""));
+        assert!(is_header_value("a\tb"));
+        assert!(is_header_value(&b"caf\xE9"[..]));
+        assert!(!is_header_value(" a"));
+        assert!(!is_header_value("a\t"));
+        assert!(!is_header_value("a\r\n b"));
+        assert!(!is_header_value("a\0"));
+        assert!(!is_header_value("\x7F"));
+    }
+
+    #[test]
+    fn cookies() {
+        assert!(is_cookie_name("SID"));
+        assert!(!is_cookie_name(""));
+        assert!(!is_cookie_name("S=D"));
+
+        assert!(is_cookie_value(""));
+        assert!(is_cookie_value("\"\""));
+        assert!(is_cookie_value("31d4d96e407aad42"));
+        assert!(is_cookie_value("\"a=b/c\""));
+        assert!(!is_cookie_value("\""));
+        assert!(!is_cookie_value("\"a"));
+        assert!(!is_cookie_value("a\"b\""));
+        assert!(!is_cookie_value("a b"));
+        assert!(!is_cookie_value("a;b"));
+        assert!(!is_cookie_value("a,b"));
+        assert!(!is_cookie_value("a\\b"));
+        assert!(!is_cookie_value(&b"\x80"[..]));
+    }
+
+    #[test]
+    fn quoted_string_chars() {
+        assert!(HttpChars::check_all("a \t(b)", QuotedStringChar));
+        assert!(!HttpChars::check_all("\"", QuotedStringChar));
+        assert!(!HttpChars::check_all("\\", QuotedStringChar));
+        assert!(HttpChars::check_all("\"\\", QuotedPair));
+    }
+}
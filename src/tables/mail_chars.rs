@@ -0,0 +1,271 @@
+#[cfg(feature = "mail-chars")]
+use core::str;
+
+#[cfg(feature = "mail-chars")]
+use _impl::Table;
+
+#[cfg(feature = "mail-chars")]
+new_table! {
+
+    pub flags {
+        /// atext: ALPHA / DIGIT / "!" / "#" / "$" / "%" / "&" / "'" / "*" / "+" /
+        /// "-" / "/" / "=" / "?" / "^" / "_" / "`" / "{" / "|" / "}" / "~"
+        AText = AT,
+        /// the bytes of dot-atom-text: atext / "."
+        DotAtomText = DA,
+        /// specials: "(" / ")" / "<" / ">" / "[" / "]" / ":" / ";" / "@" / "\\" /
+        /// "," / "." / DQUOTE
+        Specials = SP,
+        /// dtext: %d33-90 / %d94-126
+        DText = DT,
+        /// qtext: %d33 / %d35-91 / %d93-126
+        QText = QT,
+        /// ctext: %d33-39 / %d42-91 / %d93-126
+        CText = CT,
+        /// obs-NO-WS-CTL: %d1-8 / %d11 / %d12 / %d14-31 / %d127
+        ObsNoWsCtl = NC,
+        /// WSP: SP / HTAB
+        Wsp = WS,
+        /// the bytes of UTF8-non-ascii (RFC 6532): %x80-FF
+        Utf8NonAscii = U8
+    }
+
+    /// lookup table for all bytes wrt. mail header grammar rules (RFC 5322/6532)
+    pub struct MailChars {
+        static data: [u16; 256] = {
+            AT = ['A'..='Z', 'a'..='z', '0'..='9', '!', '#'..='\'', '*', '+', '-', '/',
+                  '=', '?', '^'..='`', '{'..='~'],
+            DA = ['A'..='Z', 'a'..='z', '0'..='9', '!', '#'..='\'', '*', '+', '-', '.', '/',
+                  '=', '?', '^'..='`', '{'..='~'],
+            SP = ['(', ')', '<', '>', '[', ']', ':', ';', '@', '\\', ',', '.', '"'],
+            DT = [33..=90, 94..=126],
+            QT = [33, 35..=91, 93..=126],
+            CT = [33..=39, 42..=91, 93..=126],
+            NC = [1..=8, 11, 12, 14..=31, 127],
+            WS = ['\t', ' '],
+            U8 = [0x80..=0xFF]
+        };
+    }
+}
+
+#[cfg(feature = "mail-chars")]
+accessor_any!{
+    /// VCHAR: %x21-7E
+    pub VChar = AText | Specials
+}
+#[cfg(feature = "mail-chars")]
+accessor_any!{
+    /// obs-qtext: qtext / obs-NO-WS-CTL
+    pub ObsQText = QText | ObsNoWsCtl
+}
+#[cfg(feature = "mail-chars")]
+accessor_any!{
+    /// obs-ctext: ctext / obs-NO-WS-CTL
+    pub ObsCText = CText | ObsNoWsCtl
+}
+#[cfg(feature = "mail-chars")]
+accessor_any!{
+    /// obs-dtext without quoted-pair: dtext / obs-NO-WS-CTL
+    pub ObsDText = DText | ObsNoWsCtl
+}
+#[cfg(feature = "mail-chars")]
+accessor_any!{
+    /// atext as extended by RFC 6532
+    pub Utf8AText = AText | Utf8NonAscii
+}
+#[cfg(feature = "mail-chars")]
+accessor_any!{
+    /// dot-atom-text bytes as extended by RFC 6532
+    pub Utf8DotAtomText = DotAtomText | Utf8NonAscii
+}
+#[cfg(feature = "mail-chars")]
+accessor_any!{
+    /// qtext as extended by RFC 6532
+    pub Utf8QText = QText | Utf8NonAscii
+}
+#[cfg(feature = "mail-chars")]
+accessor_any!{
+    /// ctext as extended by RFC 6532
+    pub Utf8CText = CText | Utf8NonAscii
+}
+#[cfg(feature = "mail-chars")]
+accessor_any!{
+    /// dtext as extended by RFC 6532
+    pub Utf8DText = DText | Utf8NonAscii
+}
+#[cfg(feature = "mail-chars")]
+accessor_any!{
+    /// VCHAR as extended by RFC 6532
+    pub Utf8VChar = AText | Specials | Utf8NonAscii
+}
+
+/// the grammar the validators accept
+#[cfg(feature = "mail-chars")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Charset {
+    /// RFC 5322, only ascii
+    Ascii,
+    /// RFC 6532, which additionally accepts well-formed UTF-8 in atext,
+    /// qtext, ctext, dtext and quoted-pairs
+    Utf8,
+}
+
+#[cfg(feature = "mail-chars")]
+impl Charset {
+
+    fn allows(self, byte: u8) -> bool {
+        self == Charset::Utf8 && MailChars::check_at(byte as usize, Utf8NonAscii)
+    }
+
+    fn is_well_formed(self, input: &[u8]) -> bool {
+        self == Charset::Ascii || str::from_utf8(input).is_ok()
+    }
+}
+
+/// true if `input` is a dot-atom-text, i.e. atoms separated by single dots
+///
+/// CFWS around the dot-atom is not accepted.
+#[cfg(feature = "mail-chars")]
+pub fn is_dot_atom<B: AsRef<[u8]> + ?Sized>(input: &B, charset: Charset) -> bool {
+    let input = input.as_ref();
+    !input.is_empty()
+        && input.split(|&byte| byte == b'.').all(|atom| {
+            !atom.is_empty() && atom.iter()
+                .all(|&byte| MailChars::check_at(byte as usize, AText) || charset.allows(byte))
+        })
+        && charset.is_well_formed(input)
+}
+
+/// true if `input` is a quoted-string including its DQUOTEs
+///
+/// Folding white space inside of the quotes is accepted, CFWS around the
+/// quotes and the obsolete syntax are not.
+#[cfg(feature = "mail-chars")]
+pub fn is_quoted_string<B: AsRef<[u8]> + ?Sized>(input: &B, charset: Charset) -> bool {
+    let content = match input.as_ref() {
+        [b'"', content @ .., b'"'] => content,
+        _ => return false
+    };
+    let is_wsp = |byte: Option<&u8>| byte
+        .map(|&byte| MailChars::check_at(byte as usize, Wsp))
+        .unwrap_or(false);
+    let mut idx = 0;
+    while idx < content.len() {
+        let byte = content[idx];
+        if byte == b'\\' {
+            match content.get(idx + 1) {
+                Some(&quoted) if MailChars::check_at(quoted as usize, VChar)
+                    || is_wsp(Some(&quoted))
+                    || charset.allows(quoted) => idx += 2,
+                _ => return false
+            }
+        } else if byte == b'\r' {
+            // the CRLF of folding white space has to be followed by WSP
+            if content.get(idx + 1) != Some(&b'\n') || !is_wsp(content.get(idx + 2)) {
+                return false;
+            }
+            idx += 3;
+        } else if MailChars::check_at(byte as usize, QText) || is_wsp(Some(&byte)) || charset.allows(byte) {
+            idx += 1;
+        } else {
+            return false;
+        }
+    }
+    charset.is_well_formed(content)
+}
+
+/// true if `input` is a local-part, i.e. a dot-atom or a quoted-string
+///
+/// The obsolete syntax (obs-local-part) is not accepted.
+#[cfg(feature = "mail-chars")]
+pub fn is_local_part<B: AsRef<[u8]> + ?Sized>(input: &B, charset: Charset) -> bool {
+    is_dot_atom(input, charset) || is_quoted_string(input, charset)
+}
+
+#[cfg(all(test, feature = "mail-chars"))]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn flags_match_rfc_grammar() {
+        use testing::ReferenceCheck;
+
+        fn is_atext(b: u8) -> bool {
+            b.is_ascii_alphanumeric() || b"!#$%&'*+-/=?^_`{|}~".contains(&b)
+        }
+
+        ReferenceCheck::<MailChars>::new()
+            .flag(AText, is_atext)
+            .flag(DotAtomText, |b| is_atext(b) || b == b'.')
+            .flag(Specials, |b| b"()<>[]:;@\\,.\"".contains(&b))
+            .flag(DText, |b| matches!(b, 33..=90 | 94..=126))
+            .flag(QText, |b| matches!(b, 33 | 35..=91 | 93..=126))
+            .flag(CText, |b| matches!(b, 33..=39 | 42..=91 | 93..=126))
+            .flag(ObsNoWsCtl, |b| matches!(b, 1..=8 | 11 | 12 | 14..=31 | 127))
+            .flag(Wsp, |b| b == b' ' || b == b'\t')
+            .flag(Utf8NonAscii, |b| b >= 0x80)
+            .assert_matches();
+    }
+
+    #[test]
+    fn vchar_covers_all_visible_chars() {
+        for byte in 0..=255u8 {
+            let is_vchar = (0x21..=0x7E).contains(&byte);
+            assert_eq!(MailChars::check_at(byte as usize, VChar), is_vchar, "{}", byte);
+            assert_eq!(MailChars::check_at(byte as usize, Utf8VChar), is_vchar || byte >= 0x80);
+        }
+    }
+
+    #[test]
+    fn obsolete_variants() {
+        assert!(MailChars::check_all("a\x01\x7F", ObsQText));
+        assert!(!MailChars::check_all("\x01", QText));
+        assert!(MailChars::check_all("(\x0B", ObsDText));
+        assert!(!MailChars::check_all("(", ObsCText));
+        assert!(!MailChars::check_all("\r", ObsCText));
+    }
+
+    #[test]
+    fn dot_atoms() {
+        assert!(is_dot_atom("john.doe", Charset::Ascii));
+        assert!(is_dot_atom("a!#$%&'*+-/=?^_`{|}~", Charset::Ascii));
+        assert!(!is_dot_atom("", Charset::Ascii));
+        assert!(!is_dot_atom(".a", Charset::Ascii));
+        assert!(!is_dot_atom("a.", Charset::Ascii));
+        assert!(!is_dot_atom("a..b", Charset::Ascii));
+        assert!(!is_dot_atom("a b", Charset::Ascii));
+        assert!(!is_dot_atom("a@b", Charset::Ascii));
+        assert!(!is_dot_atom("jörg", Charset::Ascii));
+        assert!(is_dot_atom("jörg.müller", Charset::Utf8));
+        assert!(!is_dot_atom(&b"j\xF6rg"[..], Charset::Utf8));
+    }
+
+    #[test]
+    fn quoted_strings() {
+        assert!(is_quoted_string("\"\"", Charset::Ascii));
+        assert!(is_quoted_string("\"john doe\"", Charset::Ascii));
+        assert!(is_quoted_string("\"a\\\"b\\\\c\"", Charset::Ascii));
+        assert!(is_quoted_string("\"a\r\n b\"", Charset::Ascii));
+        assert!(!is_quoted_string("\"", Charset::Ascii));
+        assert!(!is_quoted_string("\"a", Charset::Ascii));
+        assert!(!is_quoted_string("a\"\"", Charset::Ascii));
+        assert!(!is_quoted_string("\"a\"b\"", Charset::Ascii));
+        assert!(!is_quoted_string("\"a\\\"", Charset::Ascii));
+        assert!(!is_quoted_string("\"a\r\nb\"", Charset::Ascii));
+        assert!(!is_quoted_string("\"a\rb\"", Charset::Ascii));
+        assert!(!is_quoted_string("\"a\x01\"", Charset::Ascii));
+        assert!(!is_quoted_string("\"ä\"", Charset::Ascii));
+        assert!(is_quoted_string("\"ä \\ö\"", Charset::Utf8));
+        assert!(!is_quoted_string(&b"\"\xE4\""[..], Charset::Utf8));
+    }
+
+    #[test]
+    fn local_parts() {
+        assert!(is_local_part("john.doe", Charset::Ascii));
+        assert!(is_local_part("\"john..doe\"", Charset::Ascii));
+        assert!(!is_local_part("john..doe", Charset::Ascii));
+        assert!(!is_local_part("john doe", Charset::Ascii));
+        assert!(is_local_part("用户", Charset::Utf8));
+    }
+}
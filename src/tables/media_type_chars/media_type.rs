@@ -0,0 +1,446 @@
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use _impl::Table;
+use super::{MediaTypeChars, Token, RestrictedToken, HttpToken, QTextWs, VCharWs, Ws};
+use super::quoting::{unquote, Unquoted};
+
+/// the grammar `MediaTypeRef::parse` accepts
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ParseMode {
+    /// RFC 6838 names and RFC 2045 parameter values
+    ///
+    /// The type, subtype and parameter names are restricted names, i.e.
+    /// they start with a letter or digit and have at most 127 chars.
+    /// Parameter values are tokens or quoted-strings. Whitespace is only
+    /// accepted around the ';' separating parameters.
+    Strict,
+    /// the media type of HTTP headers (RFC 9110)
+    ///
+    /// All names and unquoted parameter values are HTTP tokens. Empty
+    /// parameters, e.g. a trailing ';', and non-ascii bytes in quoted-strings
+    /// (obs-text) are accepted.
+    Http,
+}
+
+/// what went wrong in `MediaTypeRef::parse`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ParseErrorKind {
+    /// a type, subtype or parameter name is missing
+    ExpectedName,
+    /// a restricted name does not start with a letter or digit
+    InvalidNameStart,
+    /// a restricted name is longer than 127 chars
+    NameTooLong,
+    /// the '/' between type and subtype is missing
+    ExpectedSlash,
+    /// the '=' after a parameter name is missing
+    ExpectedEquals,
+    /// a parameter value is missing
+    ExpectedValue,
+    /// a quoted-string has no closing '"'
+    UnclosedQuotedString,
+    /// a char which is not allowed in a quoted-string
+    InvalidQuotedChar,
+    /// something else than a ';' or the end of the input follows a parameter
+    ExpectedSemicolon,
+    /// a parameter between two ';' or after the last ';' is empty
+    EmptyParameter,
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        fter.write_str(match *self {
+            ParseErrorKind::ExpectedName => "expected a name",
+            ParseErrorKind::InvalidNameStart => "name has to start with a letter or digit",
+            ParseErrorKind::NameTooLong => "name is longer than 127 chars",
+            ParseErrorKind::ExpectedSlash => "expected '/'",
+            ParseErrorKind::ExpectedEquals => "expected '='",
+            ParseErrorKind::ExpectedValue => "expected a parameter value",
+            ParseErrorKind::UnclosedQuotedString => "quoted-string is not closed",
+            ParseErrorKind::InvalidQuotedChar => "invalid char in quoted-string",
+            ParseErrorKind::ExpectedSemicolon => "expected ';'",
+            ParseErrorKind::EmptyParameter => "empty parameter",
+        })
+    }
+}
+
+/// the error returned by `MediaTypeRef::parse`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    offset: usize,
+}
+
+impl ParseError {
+
+    fn new(kind: ParseErrorKind, offset: usize) -> Self {
+        ParseError { kind, offset }
+    }
+
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+
+    /// the byte offset in the input at which parsing failed
+    ///
+    /// For an unclosed quoted-string this is the offset of the opening '"'.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        write!(fter, "{} at byte {}", self.kind, self.offset)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for ParseError {}
+
+/// a parsed media type borrowing from its input
+///
+/// # Example
+///
+/// ```
+/// use lut::media_type_chars::{MediaTypeRef, ParseMode};
+///
+/// let media_type = MediaTypeRef::parse("text/plain; charset=\"utf-8\"", ParseMode::Strict).unwrap();
+/// assert_eq!(media_type.type_(), "text");
+/// assert_eq!(media_type.subtype(), "plain");
+/// let charset = media_type.param("Charset").unwrap();
+/// assert_eq!(charset.raw_value(), "\"utf-8\"");
+/// assert_eq!(charset.value().as_str(), Some("utf-8"));
+///
+/// let err = MediaTypeRef::parse("text/plain; charset", ParseMode::Strict).unwrap_err();
+/// assert_eq!(err.to_string(), "expected '=' at byte 19");
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MediaTypeRef<'a> {
+    input: &'a str,
+    slash: usize,
+    subtype_end: usize,
+}
+
+impl<'a> MediaTypeRef<'a> {
+
+    /// parses `type/subtype` followed by any number of `; name=value` parameters
+    ///
+    /// The input has to be trimmed, no whitespace is accepted before the
+    /// type or after the last parameter.
+    pub fn parse(input: &'a str, mode: ParseMode) -> Result<Self, ParseError> {
+        let bytes = input.as_bytes();
+        let slash = name(bytes, 0, mode)?;
+        if bytes.get(slash) != Some(&b'/') {
+            return Err(ParseError::new(ParseErrorKind::ExpectedSlash, slash));
+        }
+        let subtype_end = name(bytes, slash + 1, mode)?;
+
+        let mut idx = subtype_end;
+        loop {
+            let semicolon = skip_ws(bytes, idx);
+            match bytes.get(semicolon) {
+                None if semicolon == idx => break,
+                Some(&b';') => {},
+                _ => return Err(ParseError::new(ParseErrorKind::ExpectedSemicolon, semicolon))
+            }
+            idx = skip_ws(bytes, semicolon + 1);
+            if idx == bytes.len() || bytes[idx] == b';' {
+                if mode == ParseMode::Strict {
+                    return Err(ParseError::new(ParseErrorKind::EmptyParameter, semicolon));
+                }
+                continue;
+            }
+            let equals = name(bytes, idx, mode)?;
+            if bytes.get(equals) != Some(&b'=') {
+                return Err(ParseError::new(ParseErrorKind::ExpectedEquals, equals));
+            }
+            idx = value(bytes, equals + 1, mode)?;
+        }
+        Ok(MediaTypeRef { input, slash, subtype_end })
+    }
+
+    /// the whole media type as passed to `parse`
+    pub fn as_str(&self) -> &'a str {
+        self.input
+    }
+
+    pub fn type_(&self) -> &'a str {
+        &self.input[..self.slash]
+    }
+
+    pub fn subtype(&self) -> &'a str {
+        &self.input[self.slash + 1..self.subtype_end]
+    }
+
+    /// the type and subtype without parameters, e.g. `text/plain`
+    pub fn essence(&self) -> &'a str {
+        &self.input[..self.subtype_end]
+    }
+
+    /// iterates over the parameters in the order of the input
+    pub fn params(&self) -> Params<'a> {
+        Params { rest: &self.input[self.subtype_end..] }
+    }
+
+    /// returns the first parameter with the name, compared case-insensitively
+    pub fn param(&self, name: &str) -> Option<ParamRef<'a>> {
+        self.params().find(|param| param.name.eq_ignore_ascii_case(name))
+    }
+}
+
+impl<'a> fmt::Display for MediaTypeRef<'a> {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        fter.write_str(self.input)
+    }
+}
+
+/// a parameter of a `MediaTypeRef`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ParamRef<'a> {
+    name: &'a str,
+    raw_value: &'a str,
+}
+
+impl<'a> ParamRef<'a> {
+
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// the value as in the input, including quotes and quoted-pairs
+    pub fn raw_value(&self) -> &'a str {
+        self.raw_value
+    }
+
+    pub fn is_quoted(&self) -> bool {
+        self.raw_value.starts_with('"')
+    }
+
+    /// the value with quotes and quoted-pairs removed
+    pub fn value(&self) -> Unquoted<'a> {
+        if self.is_quoted() {
+            unquote(self.raw_value).expect("quoted-string validated by the parser")
+        } else {
+            Unquoted::verbatim(self.raw_value)
+        }
+    }
+}
+
+/// iterator over the parameters of a `MediaTypeRef`, created by `MediaTypeRef::params`
+#[derive(Clone, Debug)]
+pub struct Params<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Params<'a> {
+    type Item = ParamRef<'a>;
+
+    fn next(&mut self) -> Option<ParamRef<'a>> {
+        // the parameters are already validated, so this only has to find their bounds
+        loop {
+            let bytes = self.rest.as_bytes();
+            let start = skip_ws(bytes, skip_ws(bytes, 0) + 1);
+            if start >= bytes.len() {
+                self.rest = "";
+                return None;
+            }
+            if bytes[start] == b';' {
+                self.rest = &self.rest[start..];
+                continue;
+            }
+            let equals = start + bytes[start..].iter().position(|&byte| byte == b'=')
+                .expect("parameter validated by the parser");
+            let end = if bytes.get(equals + 1) == Some(&b'"') {
+                quoted_string_end(bytes, equals + 1)
+            } else {
+                equals + 1 + bytes[equals + 1..].iter()
+                    .position(|&byte| byte == b';' || MediaTypeChars::check_at(byte as usize, Ws))
+                    .unwrap_or(bytes.len() - equals - 1)
+            };
+            let param = ParamRef { name: &self.rest[start..equals], raw_value: &self.rest[equals + 1..end] };
+            self.rest = &self.rest[end..];
+            return Some(param);
+        }
+    }
+}
+
+fn skip_ws(bytes: &[u8], idx: usize) -> usize {
+    idx + MediaTypeChars::prefix_len(bytes.get(idx..).unwrap_or(&[]), Ws)
+}
+
+/// parses the name starting at `start` and returns its end
+fn name(bytes: &[u8], start: usize, mode: ParseMode) -> Result<usize, ParseError> {
+    let rest = &bytes[start..];
+    let len = match mode {
+        ParseMode::Strict => MediaTypeChars::prefix_len(rest, RestrictedToken),
+        ParseMode::Http => MediaTypeChars::prefix_len(rest, HttpToken),
+    };
+    if len == 0 {
+        return Err(ParseError::new(ParseErrorKind::ExpectedName, start));
+    }
+    if mode == ParseMode::Strict {
+        if !rest[0].is_ascii_alphanumeric() {
+            return Err(ParseError::new(ParseErrorKind::InvalidNameStart, start));
+        }
+        if len > 127 {
+            return Err(ParseError::new(ParseErrorKind::NameTooLong, start + 127));
+        }
+    }
+    Ok(start + len)
+}
+
+/// parses the token or quoted-string starting at `start` and returns its end
+fn value(bytes: &[u8], start: usize, mode: ParseMode) -> Result<usize, ParseError> {
+    if bytes.get(start) == Some(&b'"') {
+        return quoted_string(bytes, start, mode);
+    }
+    let rest = &bytes[start..];
+    let len = match mode {
+        ParseMode::Strict => MediaTypeChars::prefix_len(rest, Token),
+        ParseMode::Http => MediaTypeChars::prefix_len(rest, HttpToken),
+    };
+    if len == 0 {
+        return Err(ParseError::new(ParseErrorKind::ExpectedValue, start));
+    }
+    Ok(start + len)
+}
+
+fn quoted_string(bytes: &[u8], start: usize, mode: ParseMode) -> Result<usize, ParseError> {
+    // obs-text is not part of the table, as it has no flags for non-ascii bytes
+    let obs_text = |byte: u8| mode == ParseMode::Http && byte >= 0x80;
+    let mut idx = start + 1;
+    loop {
+        match bytes.get(idx) {
+            None => return Err(ParseError::new(ParseErrorKind::UnclosedQuotedString, start)),
+            Some(&b'"') => return Ok(idx + 1),
+            Some(&b'\\') => match bytes.get(idx + 1) {
+                None => return Err(ParseError::new(ParseErrorKind::UnclosedQuotedString, start)),
+                Some(&quoted) if MediaTypeChars::check_at(quoted as usize, VCharWs) || obs_text(quoted) =>
+                    idx += 2,
+                Some(_) => return Err(ParseError::new(ParseErrorKind::InvalidQuotedChar, idx + 1))
+            },
+            Some(&byte) if MediaTypeChars::check_at(byte as usize, QTextWs) || obs_text(byte) => idx += 1,
+            Some(_) => return Err(ParseError::new(ParseErrorKind::InvalidQuotedChar, idx))
+        }
+    }
+}
+
+/// the end of a validated quoted-string starting at `start`
+fn quoted_string_end(bytes: &[u8], start: usize) -> usize {
+    let mut idx = start + 1;
+    while bytes[idx] != b'"' {
+        idx += if bytes[idx] == b'\\' { 2 } else { 1 };
+    }
+    idx + 1
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(input: &str) -> Result<MediaTypeRef<'_>, ParseError> {
+        MediaTypeRef::parse(input, ParseMode::Strict)
+    }
+
+    fn parse_http(input: &str) -> Result<MediaTypeRef<'_>, ParseError> {
+        MediaTypeRef::parse(input, ParseMode::Http)
+    }
+
+    fn err(kind: ParseErrorKind, offset: usize) -> Result<MediaTypeRef<'static>, ParseError> {
+        Err(ParseError::new(kind, offset))
+    }
+
+    fn params<'a>(media_type: &MediaTypeRef<'a>) -> Vec<(&'a str, &'a str)> {
+        media_type.params().map(|param| (param.name(), param.raw_value())).collect()
+    }
+
+    #[test]
+    fn type_and_subtype() {
+        let media_type = parse("application/vnd.api+json").unwrap();
+        assert_eq!(media_type.type_(), "application");
+        assert_eq!(media_type.subtype(), "vnd.api+json");
+        assert_eq!(media_type.essence(), "application/vnd.api+json");
+        assert_eq!(media_type.params().next(), None);
+        assert_eq!(media_type.to_string(), "application/vnd.api+json");
+    }
+
+    #[test]
+    fn parameters() {
+        let media_type = parse("multipart/mixed;boundary=\"a \\\"b\\\"; c\"  ;  charset=utf-8").unwrap();
+        assert_eq!(media_type.essence(), "multipart/mixed");
+        assert_eq!(params(&media_type), vec![("boundary", "\"a \\\"b\\\"; c\""), ("charset", "utf-8")]);
+        let boundary = media_type.param("BOUNDARY").unwrap();
+        assert!(boundary.is_quoted());
+        assert_eq!(boundary.value().to_string(), "a \"b\"; c");
+        let charset = media_type.param("charset").unwrap();
+        assert!(!charset.is_quoted());
+        assert_eq!(charset.value().as_str(), Some("utf-8"));
+        assert_eq!(media_type.param("name"), None);
+    }
+
+    #[test]
+    fn first_parameter_with_a_name_is_returned() {
+        let media_type = parse("text/plain; a=1; A=2").unwrap();
+        assert_eq!(media_type.param("a").unwrap().raw_value(), "1");
+    }
+
+    #[test]
+    fn errors_carry_the_offset() {
+        assert_eq!(parse(""), err(ParseErrorKind::ExpectedName, 0));
+        assert_eq!(parse("/plain"), err(ParseErrorKind::ExpectedName, 0));
+        assert_eq!(parse("text"), err(ParseErrorKind::ExpectedSlash, 4));
+        assert_eq!(parse("text plain"), err(ParseErrorKind::ExpectedSlash, 4));
+        assert_eq!(parse("text/"), err(ParseErrorKind::ExpectedName, 5));
+        assert_eq!(parse("text/plain "), err(ParseErrorKind::ExpectedSemicolon, 11));
+        assert_eq!(parse("text/plain;"), err(ParseErrorKind::EmptyParameter, 10));
+        assert_eq!(parse("text/plain; a=1;; b=2"), err(ParseErrorKind::EmptyParameter, 15));
+        assert_eq!(parse("text/plain; =1"), err(ParseErrorKind::ExpectedName, 12));
+        assert_eq!(parse("text/plain; a"), err(ParseErrorKind::ExpectedEquals, 13));
+        assert_eq!(parse("text/plain; a =1"), err(ParseErrorKind::ExpectedEquals, 13));
+        assert_eq!(parse("text/plain; a="), err(ParseErrorKind::ExpectedValue, 14));
+        assert_eq!(parse("text/plain; a=b c"), err(ParseErrorKind::ExpectedSemicolon, 16));
+        assert_eq!(parse("text/plain; a=\"b"), err(ParseErrorKind::UnclosedQuotedString, 14));
+        assert_eq!(parse("text/plain; a=\"b\\\""), err(ParseErrorKind::UnclosedQuotedString, 14));
+        assert_eq!(parse("text/plain; a=\"b\\"), err(ParseErrorKind::UnclosedQuotedString, 14));
+        assert_eq!(parse("text/plain; a=\"b\rc\""), err(ParseErrorKind::InvalidQuotedChar, 16));
+        assert_eq!(parse("text/plain; a=\"b\\\rc\""), err(ParseErrorKind::InvalidQuotedChar, 17));
+        assert_eq!(parse("text/plain; a=\"b\"c"), err(ParseErrorKind::ExpectedSemicolon, 17));
+    }
+
+    #[test]
+    fn strict_mode_uses_restricted_names() {
+        assert_eq!(parse("+text/plain"), err(ParseErrorKind::InvalidNameStart, 0));
+        assert_eq!(parse("text/plain; _a=b"), err(ParseErrorKind::InvalidNameStart, 12));
+        assert_eq!(parse("te*t/plain"), err(ParseErrorKind::ExpectedSlash, 2));
+        let long = format!("text/{}", "a".repeat(128));
+        assert_eq!(parse(&long), err(ParseErrorKind::NameTooLong, 5 + 127));
+        assert!(parse(&long[..long.len() - 1]).is_ok());
+        // values are RFC 2045 tokens
+        assert!(parse("text/plain; a={b}").is_ok());
+        assert_eq!(parse("text/plain; a=ä"), err(ParseErrorKind::ExpectedValue, 14));
+        assert_eq!(parse("text/plain; a=\"ä\""), err(ParseErrorKind::InvalidQuotedChar, 15));
+    }
+
+    #[test]
+    fn http_mode_is_lenient() {
+        assert!(parse_http("*/*").is_ok());
+        assert!(parse_http("text/plain;").is_ok());
+        assert!(parse_http("text/x_*; _a=b").is_ok());
+        let media_type = parse_http("text/plain; ; a=\"ä\" ;;b=c; ").unwrap();
+        assert_eq!(params(&media_type), vec![("a", "\"ä\""), ("b", "c")]);
+        assert_eq!(media_type.param("a").unwrap().value().as_str(), Some("ä"));
+        assert_eq!(parse_http("text/plain; a={b}"), err(ParseErrorKind::ExpectedValue, 14));
+        assert!(parse("*/*").is_err());
+    }
+
+    #[test]
+    fn error_messages() {
+        assert_eq!(parse("text").unwrap_err().to_string(), "expected '/' at byte 4");
+        assert_eq!(parse("text/plain; a=\"b").unwrap_err().to_string(),
+            "quoted-string is not closed at byte 14");
+    }
+}
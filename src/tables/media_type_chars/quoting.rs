@@ -0,0 +1,254 @@
+//! quoting and unquoting of media type parameter values
+//!
+//! A parameter value is either a token or a quoted-string, in which
+//! '"' and '\\' have to be escaped by a preceding '\\' (a quoted-pair).
+use core::fmt;
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use _impl::Table;
+use super::{MediaTypeChars, Token, DQuoteOrEscape};
+
+/// true if `value` has to be quoted to be used as parameter value
+///
+/// This is the case if it is empty or not a token (RFC 2045).
+pub fn needs_quoting(value: &str) -> bool {
+    value.is_empty() || !MediaTypeChars::check_all(value, Token)
+}
+
+/// escapes all '"' and '\\' in `value` with a '\\'
+///
+/// The escaped value is produced by the `Display` implementation of the
+/// returned value. Other chars, including ones which are not valid in a
+/// quoted-string, are written as is.
+pub fn escape(value: &str) -> Escaped<'_> {
+    Escaped { value }
+}
+
+/// escapes `value` and encloses it in '"', see `escape`
+///
+/// # Example
+///
+/// ```
+/// use lut::media_type_chars::quoting::{needs_quoting, quote};
+///
+/// let value = "say \"hi\"";
+/// assert!(needs_quoting(value));
+/// assert_eq!(quote(value).to_string(), r#""say \"hi\"""#);
+/// ```
+pub fn quote(value: &str) -> Quoted<'_> {
+    Quoted { value }
+}
+
+/// an escaped value, created by `escape`
+#[derive(Copy, Clone, Debug)]
+pub struct Escaped<'a> {
+    value: &'a str,
+}
+
+impl<'a> fmt::Display for Escaped<'a> {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        let mut rest = self.value;
+        while let Some(idx) = MediaTypeChars::find_first_match(rest, DQuoteOrEscape) {
+            fter.write_str(&rest[..idx])?;
+            fter.write_str("\\")?;
+            fter.write_str(&rest[idx..idx + 1])?;
+            rest = &rest[idx + 1..];
+        }
+        fter.write_str(rest)
+    }
+}
+
+/// a quoted value, created by `quote`
+#[derive(Copy, Clone, Debug)]
+pub struct Quoted<'a> {
+    value: &'a str,
+}
+
+impl<'a> fmt::Display for Quoted<'a> {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        write!(fter, "\"{}\"", escape(self.value))
+    }
+}
+
+/// the reason `unquote` failed
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum UnquoteError {
+    /// the input does not start and end with a '"'
+    MissingQuotes,
+    /// a '"' at the given index of the input is not escaped
+    UnescapedQuote { index: usize },
+    /// the closing '"' is escaped
+    EscapedClosingQuote,
+}
+
+impl fmt::Display for UnquoteError {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            UnquoteError::MissingQuotes =>
+                write!(fter, "the value is not enclosed in quotes"),
+            UnquoteError::UnescapedQuote { index } =>
+                write!(fter, "the quote at index {} is not escaped", index),
+            UnquoteError::EscapedClosingQuote =>
+                write!(fter, "the closing quote is escaped")
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for UnquoteError {}
+
+/// removes the enclosing '"' from `input` and resolves its quoted-pairs
+///
+/// Only the placement of '"' and '\\' is validated, other chars are
+/// accepted as is.
+///
+/// # Example
+///
+/// ```
+/// use lut::media_type_chars::quoting::unquote;
+///
+/// let unquoted = unquote(r#""say \"hi\"""#).unwrap();
+/// assert_eq!(unquoted.to_string(), "say \"hi\"");
+/// assert_eq!(unquote("\"plain\"").unwrap().as_str(), Some("plain"));
+/// ```
+pub fn unquote(input: &str) -> Result<Unquoted<'_>, UnquoteError> {
+    let bytes = input.as_bytes();
+    if bytes.len() < 2 || bytes[0] != b'"' || bytes[bytes.len() - 1] != b'"' {
+        return Err(UnquoteError::MissingQuotes);
+    }
+    let content = &input[1..input.len() - 1];
+    let mut has_quoted_pairs = false;
+    let mut idx = 0;
+    while let Some(offset) = MediaTypeChars::find_first_match(&content[idx..], DQuoteOrEscape) {
+        let pos = idx + offset;
+        if content.as_bytes()[pos] == b'"' {
+            return Err(UnquoteError::UnescapedQuote { index: pos + 1 });
+        }
+        let quoted = content[pos + 1..].chars().next().ok_or(UnquoteError::EscapedClosingQuote)?;
+        idx = pos + 1 + quoted.len_utf8();
+        has_quoted_pairs = true;
+    }
+    Ok(Unquoted { content, has_quoted_pairs })
+}
+
+/// an unquoted value, created by `unquote`
+///
+/// The value is produced by the `Display` implementation, `as_str`
+/// returns it without copying if it does not contain quoted-pairs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Unquoted<'a> {
+    content: &'a str,
+    has_quoted_pairs: bool,
+}
+
+impl<'a> Unquoted<'a> {
+
+    /// a value which was not quoted, e.g. a token
+    pub(crate) fn verbatim(content: &'a str) -> Self {
+        Unquoted { content, has_quoted_pairs: false }
+    }
+
+    /// the value without enclosing quotes, with the quoted-pairs still in place
+    pub fn raw(&self) -> &'a str {
+        self.content
+    }
+
+    /// the value if it is the same as the raw content
+    pub fn as_str(&self) -> Option<&'a str> {
+        if self.has_quoted_pairs { None } else { Some(self.content) }
+    }
+
+    /// the value, only allocating if it contains quoted-pairs
+    #[cfg(feature = "std")]
+    pub fn into_cow(self) -> Cow<'a, str> {
+        match self.as_str() {
+            Some(value) => Cow::Borrowed(value),
+            None => Cow::Owned(self.to_string())
+        }
+    }
+}
+
+impl<'a> fmt::Display for Unquoted<'a> {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        let mut rest = self.content;
+        // `unquote` made sure every '\\' is followed by a char and '"' only appears escaped
+        while let Some(idx) = MediaTypeChars::find_first_match(rest, DQuoteOrEscape) {
+            fter.write_str(&rest[..idx])?;
+            let quoted = &rest[idx + 1..];
+            let len = quoted.chars().next().map(char::len_utf8).unwrap_or(0);
+            fter.write_str(&quoted[..len])?;
+            rest = &quoted[len..];
+        }
+        fter.write_str(rest)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn needs_quoting_non_tokens() {
+        assert!(!needs_quoting("utf-8"));
+        assert!(!needs_quoting("a!#$%&'*+-.^_`{|}~"));
+        assert!(needs_quoting(""));
+        assert!(needs_quoting("a b"));
+        assert!(needs_quoting("a/b"));
+        assert!(needs_quoting("a=b"));
+        assert!(needs_quoting("\""));
+        assert!(needs_quoting("ä"));
+    }
+
+    #[test]
+    fn escape_and_quote() {
+        assert_eq!(escape("").to_string(), "");
+        assert_eq!(escape("a b").to_string(), "a b");
+        assert_eq!(escape("\"\\").to_string(), "\\\"\\\\");
+        assert_eq!(escape("ä\"ö").to_string(), "ä\\\"ö");
+        assert_eq!(quote("").to_string(), "\"\"");
+        assert_eq!(quote("a\\b").to_string(), "\"a\\\\b\"");
+    }
+
+    #[test]
+    fn unquote_values() {
+        assert_eq!(unquote("\"\"").unwrap().as_str(), Some(""));
+        assert_eq!(unquote("\"a b\"").unwrap().as_str(), Some("a b"));
+        let unquoted = unquote("\"a\\\"b\\\\c\\d\\ä\"").unwrap();
+        assert_eq!(unquoted.as_str(), None);
+        assert_eq!(unquoted.raw(), "a\\\"b\\\\c\\d\\ä");
+        assert_eq!(unquoted.to_string(), "a\"b\\cdä");
+    }
+
+    #[test]
+    fn unquote_errors() {
+        assert_eq!(unquote(""), Err(UnquoteError::MissingQuotes));
+        assert_eq!(unquote("\""), Err(UnquoteError::MissingQuotes));
+        assert_eq!(unquote("a\""), Err(UnquoteError::MissingQuotes));
+        assert_eq!(unquote("\"a"), Err(UnquoteError::MissingQuotes));
+        assert_eq!(unquote("\"a\"b\""), Err(UnquoteError::UnescapedQuote { index: 2 }));
+        assert_eq!(unquote("\"a\\\""), Err(UnquoteError::EscapedClosingQuote));
+        assert_eq!(unquote("\"a\\\\\"").unwrap().to_string(), "a\\");
+    }
+
+    #[test]
+    fn quote_unquote_roundtrip() {
+        for &value in &["", "a", "\"", "\\", "\\\"", "a \"b\" \\c\\", "ä\\ö"] {
+            let quoted = quote(value).to_string();
+            assert_eq!(unquote(&quoted).unwrap().to_string(), value);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn into_cow() {
+        match unquote("\"a\"").unwrap().into_cow() {
+            Cow::Borrowed(value) => assert_eq!(value, "a"),
+            Cow::Owned(_) => panic!("unexpected allocation")
+        }
+        assert_eq!(unquote("\"\\a\"").unwrap().into_cow(), "a");
+    }
+}
@@ -0,0 +1,345 @@
+#[cfg(feature = "uri-chars")]
+use core::{fmt, str};
+
+#[cfg(feature = "uri-chars")]
+use _impl::Table;
+
+#[cfg(feature = "uri-chars")]
+new_table! {
+
+    pub flags {
+        /// unreserved: ALPHA / DIGIT / "-" / "." / "_" / "~"
+        Unreserved = UR,
+        /// gen-delims: ":" / "/" / "?" / "#" / "[" / "]" / "@"
+        GenDelims = GD,
+        /// sub-delims: "!" / "$" / "&" / "'" / "(" / ")" / "*" / "+" / "," / ";" / "="
+        SubDelims = SD,
+        /// pchar without pct-encoded: unreserved / sub-delims / ":" / "@"
+        PChar = PC,
+        /// query and fragment without pct-encoded: pchar / "/" / "?"
+        QueryOrFragment = QF,
+        /// userinfo without pct-encoded: unreserved / sub-delims / ":"
+        UserInfo = UI,
+        /// scheme: ALPHA / DIGIT / "+" / "-" / "."
+        ///
+        /// The first char of a scheme has to be an ALPHA.
+        Scheme = SC,
+        /// HEXDIG, including lowercase letters
+        HexDig = HD
+    }
+
+    /// lookup table for all bytes wrt. URI grammar rules
+    ///
+    /// '%' and non-ascii bytes have no flags, they only appear as part
+    /// of pct-encoded in a URI.
+    pub struct UriChars {
+        static data: [u8; 256] = {
+            UR = ['A'..='Z', 'a'..='z', '0'..='9', '-', '.', '_', '~'],
+            GD = [':', '/', '?', '#', '[', ']', '@'],
+            SD = ['!', '$', '&', '\'', '(', ')', '*', '+', ',', ';', '='],
+            PC = ['A'..='Z', 'a'..='z', '0'..='9', '-', '.', '_', '~',
+                  '!', '$', '&', '\'', '(', ')', '*', '+', ',', ';', '=', ':', '@'],
+            QF = ['A'..='Z', 'a'..='z', '0'..='9', '-', '.', '_', '~',
+                  '!', '$', '&', '\'', '(', ')', '*', '+', ',', ';', '=', ':', '@', '/', '?'],
+            UI = ['A'..='Z', 'a'..='z', '0'..='9', '-', '.', '_', '~',
+                  '!', '$', '&', '\'', '(', ')', '*', '+', ',', ';', '=', ':'],
+            SC = ['A'..='Z', 'a'..='z', '0'..='9', '+', '-', '.'],
+            HD = ['0'..='9', 'A'..='F', 'a'..='f']
+        };
+    }
+}
+
+#[cfg(feature = "uri-chars")]
+accessor_any!{
+    /// reserved: gen-delims / sub-delims
+    pub Reserved = GenDelims | SubDelims
+}
+#[cfg(feature = "uri-chars")]
+accessor_any!{
+    /// reg-name without pct-encoded: unreserved / sub-delims
+    pub RegName = Unreserved | SubDelims
+}
+#[cfg(feature = "uri-chars")]
+accessor_any!{
+    /// all chars which can appear in a URI, except the '%' of pct-encoded
+    pub UriChar = Unreserved | GenDelims | SubDelims
+}
+
+/// the part of a URI a string is percent-encoded for
+///
+/// Each component keeps the chars its grammar rule allows and encodes all
+/// other bytes, including '%'.
+#[cfg(feature = "uri-chars")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Component {
+    /// userinfo, keeps `UserInfo`
+    UserInfo,
+    /// reg-name of the host, keeps `RegName`
+    Host,
+    /// a path, keeps `PChar` and '/'
+    Path,
+    /// a single path segment, keeps `PChar`
+    PathSegment,
+    /// query, keeps `QueryOrFragment`
+    Query,
+    /// fragment, keeps `QueryOrFragment`
+    Fragment,
+}
+
+#[cfg(feature = "uri-chars")]
+impl Component {
+
+    /// true if `byte` can appear in the component without being percent-encoded
+    pub fn allows(self, byte: u8) -> bool {
+        let idx = byte as usize;
+        match self {
+            Component::UserInfo => UriChars::check_at(idx, UserInfo),
+            Component::Host => UriChars::check_at(idx, RegName),
+            Component::Path => byte == b'/' || UriChars::check_at(idx, PChar),
+            Component::PathSegment => UriChars::check_at(idx, PChar),
+            Component::Query | Component::Fragment => UriChars::check_at(idx, QueryOrFragment),
+        }
+    }
+
+    /// true if `input` is a valid instance of the component
+    ///
+    /// All bytes have to be allowed by the component or be part of a
+    /// pct-encoded, i.e. a '%' followed by two hex digits.
+    pub fn is_valid<B: AsRef<[u8]> + ?Sized>(self, input: &B) -> bool {
+        let input = input.as_ref();
+        let mut idx = 0;
+        while idx < input.len() {
+            if input[idx] == b'%' {
+                if !is_hex_dig_at(input, idx + 1) || !is_hex_dig_at(input, idx + 2) {
+                    return false;
+                }
+                idx += 3;
+            } else if self.allows(input[idx]) {
+                idx += 1;
+            } else {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(feature = "uri-chars")]
+fn is_hex_dig_at(input: &[u8], idx: usize) -> bool {
+    input.get(idx).map(|&byte| UriChars::check_at(byte as usize, HexDig)).unwrap_or(false)
+}
+
+/// percent-encodes `input` for use in `component`
+///
+/// Uses uppercase hex digits as recommended by RFC 3986. The encoded
+/// string is produced by the `Display` implementation of the returned
+/// value, so no allocation is needed.
+///
+/// # Example
+///
+/// ```
+/// use lut::uri_chars::{percent_encode, Component};
+///
+/// let encoded = percent_encode("a b/ä", Component::PathSegment).to_string();
+/// assert_eq!(encoded, "a%20b%2F%C3%A4");
+/// ```
+#[cfg(feature = "uri-chars")]
+pub fn percent_encode<B: AsRef<[u8]> + ?Sized>(input: &B, component: Component) -> PercentEncode<'_> {
+    PercentEncode { input: input.as_ref(), component }
+}
+
+/// percent-encoded input, created by `percent_encode`
+#[cfg(feature = "uri-chars")]
+#[derive(Copy, Clone, Debug)]
+pub struct PercentEncode<'a> {
+    input: &'a [u8],
+    component: Component,
+}
+
+#[cfg(feature = "uri-chars")]
+impl<'a> PercentEncode<'a> {
+
+    /// true if at least one byte of the input has to be encoded
+    pub fn needs_encoding(&self) -> bool {
+        self.input.iter().any(|&byte| !self.component.allows(byte))
+    }
+}
+
+#[cfg(feature = "uri-chars")]
+impl<'a> fmt::Display for PercentEncode<'a> {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        let mut rest = self.input;
+        while !rest.is_empty() {
+            let allowed = rest.iter()
+                .position(|&byte| !self.component.allows(byte))
+                .unwrap_or(rest.len());
+            let (chunk, tail) = rest.split_at(allowed);
+            fter.write_str(str::from_utf8(chunk).expect("allowed bytes are ascii"))?;
+            rest = match tail.split_first() {
+                Some((&byte, tail)) => {
+                    write!(fter, "%{:02X}", byte)?;
+                    tail
+                },
+                None => tail
+            };
+        }
+        Ok(())
+    }
+}
+
+/// decodes all pct-encoded in `input`
+///
+/// A '%' which is not followed by two hex digits is kept as is.
+///
+/// # Example
+///
+/// ```
+/// use lut::uri_chars::percent_decode;
+///
+/// let decoded = percent_decode("a%20b%2f%C3%A4%").collect::<Vec<u8>>();
+/// assert_eq!(decoded, "a b/ä%".as_bytes());
+/// ```
+#[cfg(feature = "uri-chars")]
+pub fn percent_decode<B: AsRef<[u8]> + ?Sized>(input: &B) -> PercentDecode<'_> {
+    PercentDecode { input: input.as_ref() }
+}
+
+/// iterator over the decoded bytes of percent-encoded input, created by `percent_decode`
+#[cfg(feature = "uri-chars")]
+#[derive(Clone, Debug)]
+pub struct PercentDecode<'a> {
+    input: &'a [u8],
+}
+
+#[cfg(feature = "uri-chars")]
+impl<'a> Iterator for PercentDecode<'a> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let (&byte, rest) = self.input.split_first()?;
+        if byte == b'%' && is_hex_dig_at(self.input, 1) && is_hex_dig_at(self.input, 2) {
+            self.input = &self.input[3..];
+            Some(hex_value(rest[0]) << 4 | hex_value(rest[1]))
+        } else {
+            self.input = rest;
+            Some(byte)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.input.len();
+        (len.div_ceil(3), Some(len))
+    }
+}
+
+#[cfg(feature = "uri-chars")]
+fn hex_value(byte: u8) -> u8 {
+    match byte {
+        b'0'..=b'9' => byte - b'0',
+        b'A'..=b'F' => byte - b'A' + 10,
+        _ => byte - b'a' + 10,
+    }
+}
+
+#[cfg(all(test, feature = "uri-chars"))]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn flags_match_rfc_grammar() {
+        use testing::ReferenceCheck;
+
+        fn is_unreserved(b: u8) -> bool {
+            b.is_ascii_alphanumeric() || b"-._~".contains(&b)
+        }
+        fn is_sub_delim(b: u8) -> bool {
+            b"!$&'()*+,;=".contains(&b)
+        }
+        fn is_pchar(b: u8) -> bool {
+            is_unreserved(b) || is_sub_delim(b) || b == b':' || b == b'@'
+        }
+
+        ReferenceCheck::<UriChars>::new()
+            .flag(Unreserved, is_unreserved)
+            .flag(GenDelims, |b| b":/?#[]@".contains(&b))
+            .flag(SubDelims, is_sub_delim)
+            .flag(PChar, is_pchar)
+            .flag(QueryOrFragment, |b| is_pchar(b) || b == b'/' || b == b'?')
+            .flag(UserInfo, |b| is_unreserved(b) || is_sub_delim(b) || b == b':')
+            .flag(Scheme, |b| b.is_ascii_alphanumeric() || b"+-.".contains(&b))
+            .flag(HexDig, |b| b.is_ascii_hexdigit())
+            .assert_matches();
+    }
+
+    #[test]
+    fn accessors() {
+        assert!(UriChars::check_all("!#$&'()*+,/:;=?@[]", Reserved));
+        assert!(!UriChars::check_all("a", Reserved));
+        assert!(UriChars::check_all("www.example-1.com", RegName));
+        assert!(!UriChars::check_all("a:b", RegName));
+        assert!(UriChars::check_all("http://user@example.com/a?b#c", UriChar));
+        assert!(!UriChars::check_all("%", UriChar));
+        assert!(!UriChars::check_all(" ", UriChar));
+    }
+
+    #[test]
+    fn components_allow_their_chars() {
+        assert!(Component::Path.allows(b'/'));
+        assert!(!Component::PathSegment.allows(b'/'));
+        assert!(Component::Query.allows(b'?'));
+        assert!(Component::Fragment.allows(b'/'));
+        assert!(!Component::Query.allows(b'#'));
+        assert!(Component::UserInfo.allows(b':'));
+        assert!(!Component::UserInfo.allows(b'@'));
+        assert!(!Component::Host.allows(b':'));
+        for &component in &[Component::UserInfo, Component::Host, Component::Path,
+                            Component::PathSegment, Component::Query, Component::Fragment] {
+            assert!(!component.allows(b'%'));
+            assert!(!component.allows(0xC3));
+            assert!(component.allows(b'~'));
+        }
+    }
+
+    #[test]
+    fn encode() {
+        assert_eq!(percent_encode("", Component::Path).to_string(), "");
+        assert_eq!(percent_encode("/a b/c%d", Component::Path).to_string(), "/a%20b/c%25d");
+        assert_eq!(percent_encode("a?b#c", Component::Query).to_string(), "a?b%23c");
+        assert_eq!(percent_encode("user:p@ss", Component::UserInfo).to_string(), "user:p%40ss");
+        assert_eq!(percent_encode(&[0x00, 0xFF][..], Component::Host).to_string(), "%00%FF");
+        assert!(!percent_encode("a/b", Component::Path).needs_encoding());
+        assert!(percent_encode("a/b", Component::PathSegment).needs_encoding());
+    }
+
+    #[test]
+    fn decode() {
+        let decode = |input: &str| percent_decode(input).collect::<Vec<u8>>();
+        assert_eq!(decode(""), b"");
+        assert_eq!(decode("%41%2f%2F"), b"A//");
+        assert_eq!(decode("%4"), b"%4");
+        assert_eq!(decode("%%41"), b"%A");
+        assert_eq!(decode("%g1x"), b"%g1x");
+    }
+
+    #[test]
+    fn encoded_input_is_valid_and_decodes_to_the_input() {
+        let input = "ä /?#[]@%!$&'()*+,;=:-._~\u{0}";
+        for &component in &[Component::UserInfo, Component::Host, Component::Path,
+                            Component::PathSegment, Component::Query, Component::Fragment] {
+            let encoded = percent_encode(input, component).to_string();
+            assert!(component.is_valid(&encoded), "{:?}: {}", component, encoded);
+            assert_eq!(percent_decode(&encoded).collect::<Vec<u8>>(), input.as_bytes());
+        }
+    }
+
+    #[test]
+    fn validate() {
+        assert!(Component::Path.is_valid("/a%20b/"));
+        assert!(!Component::Path.is_valid("/a b"));
+        assert!(!Component::Path.is_valid("%2"));
+        assert!(!Component::Path.is_valid("%2x"));
+        assert!(!Component::PathSegment.is_valid("a/b"));
+        assert!(Component::Host.is_valid(""));
+    }
+}
@@ -0,0 +1,176 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use _impl::{Table, TableValue, CellBit, Flag};
+use flags::FlagInfo;
+use dump::index_cell;
+
+type Predicate = Box<dyn Fn(u8) -> bool>;
+
+/// checks the flags of a table against reference predicates
+///
+/// For every registered flag the predicate is called with every byte the
+/// table contains a cell for and has to return true exactly if the flag is
+/// set in the cell. As predicates take a `u8` only the first 256 cells of
+/// larger tables are checked.
+///
+/// Requires the `testing` feature.
+///
+/// # Example
+///
+/// ```
+/// #[macro_use]
+/// extern crate lut;
+/// use lut::ReferenceCheck;
+///
+/// new_table! {
+///     flags { Digit=DI, Ws=WS }
+///     struct Chars {
+///         static data: [u8; 4] = [ DI, DI, WS, DI ];
+///     }
+/// }
+///
+/// # fn main() {
+/// let check = ReferenceCheck::<Chars>::new()
+///     .flag(Digit, |b| b != 2)
+///     .flag(Ws, |b| b == 2 || b == 3);
+///
+/// let mismatches = check.mismatches();
+/// assert_eq!(mismatches.len(), 1);
+/// assert_eq!(mismatches[0].to_string(), "3 (0x03 '\\x03'): `Ws` is not set but expected to be");
+/// # }
+/// ```
+pub struct ReferenceCheck<T: Table> {
+    references: Vec<(FlagInfo, Predicate)>,
+    mark: PhantomData<T>
+}
+
+/// a cell in which a flag does not match its reference predicate
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ReferenceMismatch {
+    pub index: usize,
+    /// the name of the flag
+    pub flag: &'static str,
+    /// the result of the reference predicate
+    pub expected: bool
+}
+
+impl<T> ReferenceCheck<T>
+    where T: Table, T::Value: CellBit
+{
+    /// creates a check without any reference predicates
+    pub fn new() -> Self {
+        ReferenceCheck { references: Vec::new(), mark: PhantomData }
+    }
+
+    /// registers `predicate` as reference for `flag`
+    pub fn flag<F, P>(mut self, _flag: F, predicate: P) -> Self
+        where F: Flag<T>, P: Fn(u8) -> bool + 'static
+    {
+        self.references.push((FlagInfo::of::<T, F>(), Box::new(predicate)));
+        self
+    }
+
+    /// returns all cells in which a flag does not match its reference
+    pub fn mismatches(&self) -> Vec<ReferenceMismatch> {
+        let len = T::len().min(256);
+        let mut mismatches = Vec::new();
+        for index in 0..len {
+            let value = T::lookup(index);
+            for (info, predicate) in &self.references {
+                let expected = predicate(index as u8);
+                let is_set = value & T::Value::bit(info.bit_index) != T::Value::ZERO;
+                if is_set != expected {
+                    mismatches.push(ReferenceMismatch { index, flag: info.name, expected });
+                }
+            }
+        }
+        mismatches
+    }
+
+    /// returns the flags of the table for which no reference was registered
+    pub fn unchecked_flags(&self) -> Vec<&'static FlagInfo> {
+        T::FLAGS.iter()
+            .filter(|flag| !self.references.iter().any(|(info, _)| info == *flag))
+            .collect()
+    }
+
+    /// panics if a flag has no reference or does not match it
+    pub fn assert_matches(&self) {
+        let unchecked = self.unchecked_flags();
+        if !unchecked.is_empty() {
+            let names = unchecked.iter().map(|flag| flag.name).collect::<Vec<_>>();
+            panic!("flags without reference: {}", names.join(", "));
+        }
+        let mismatches = self.mismatches();
+        if !mismatches.is_empty() {
+            let lines = mismatches.iter().map(|mismatch| mismatch.to_string()).collect::<Vec<_>>();
+            panic!("flags differ from reference at {} cells:\n{}", mismatches.len(), lines.join("\n"));
+        }
+    }
+}
+
+impl<T> Default for ReferenceCheck<T>
+    where T: Table, T::Value: CellBit
+{
+    fn default() -> Self {
+        ReferenceCheck::new()
+    }
+}
+
+impl fmt::Display for ReferenceMismatch {
+    fn fmt(&self, fter: &mut fmt::Formatter) -> fmt::Result {
+        let (is, expected) = if self.expected { ("not set", "be") } else { ("set", "not be") };
+        write!(fter, "{}: `{}` is {} but expected to {}", index_cell(self.index), self.flag, is, expected)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    new_table! {
+        flags { Digit=DI, Ws=WS, Other=OT }
+        struct Chars {
+            static data: [u8; 4] = [ DI, DI|OT, WS, DI ];
+        }
+    }
+
+    #[test]
+    fn mismatches() {
+        let check = ReferenceCheck::<Chars>::new()
+            .flag(Digit, |b| b < 2)
+            .flag(Ws, |b| b == 2);
+        assert_eq!(check.mismatches(), vec![
+            ReferenceMismatch { index: 3, flag: "Digit", expected: false }
+        ]);
+        assert_eq!(check.mismatches()[0].to_string(), "3 (0x03 '\\x03'): `Digit` is set but expected to not be");
+        assert_eq!(check.unchecked_flags(), vec![&Chars::FLAGS[2]]);
+    }
+
+    #[test]
+    fn assert_matches() {
+        ReferenceCheck::<Chars>::new()
+            .flag(Digit, |b| b != 2)
+            .flag(Ws, |b| b == 2)
+            .flag(Other, |b| b == 1)
+            .assert_matches();
+    }
+
+    #[test]
+    #[should_panic(expected = "flags without reference: Ws, Other")]
+    fn assert_matches_requires_all_flags() {
+        ReferenceCheck::<Chars>::new().flag(Digit, |b| b != 2).assert_matches();
+    }
+
+    #[test]
+    #[should_panic(expected = "flags differ from reference at 1 cells:\n1 (0x01 '\\x01'): `Other` is set")]
+    fn assert_matches_panics() {
+        ReferenceCheck::<Chars>::new()
+            .flag(Digit, |b| b != 2)
+            .flag(Ws, |b| b == 2)
+            .flag(Other, |_| false)
+            .assert_matches();
+    }
+}
@@ -0,0 +1,150 @@
+#![cfg(feature = "cli")]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const OLD: &str = r#"
+[[table]]
+name = "Chars"
+cell_type = "u8"
+size = 128
+flag = [
+    { name = "Token", short_name = "TO", chars = "!#{", ranges = [["a", "c"]] },
+    { name = "Ws", short_name = "WS", chars = " " },
+]
+
+[[table]]
+name = "Removed"
+cell_type = "u8"
+size = 4
+"#;
+
+const NEW: &str = r#"
+[[table]]
+name = "Chars"
+cell_type = "u16"
+size = 128
+flag = [
+    { name = "Token", short_name = "TO", chars = "!#", ranges = [["a", "c"], ["0", "9"]] },
+    { name = "Digit", short_name = "DI", ranges = [["0", "9"]] },
+]
+"#;
+
+/// writes `content` to a file in a directory of the test and returns its path
+///
+/// The directory is in cargo's temporary directory for integration tests
+/// in `target`, so repeated runs reuse it.
+fn description(test: &str, name: &str, content: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("cli-{}", test));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, content).unwrap();
+    path
+}
+
+/// runs the binary and returns its exit code, stdout and stderr
+fn lut(args: &[&str]) -> (i32, String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_lut")).args(args).output().unwrap();
+    (
+        output.status.code().unwrap(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap()
+    )
+}
+
+#[test]
+fn gen() {
+    let path = description("gen", "chars.toml", NEW);
+    let (code, out, _) = lut(&["gen", path.to_str().unwrap()]);
+    assert_eq!(code, 0);
+    assert!(out.starts_with("// generated by lut-build from `chars.toml`"), "{}", out);
+    assert!(out.contains("TO = ['!', '#', 'a'..='c', '0'..='9'],"), "{}", out);
+}
+
+#[test]
+fn show() {
+    let path = description("show", "chars.toml", OLD);
+    let (code, out, _) = lut(&["show", path.to_str().unwrap(), "Chars", "--format", "csv", "--columns"]);
+    assert_eq!(code, 0);
+    let lines = out.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "idx,hex,char,Token,Ws");
+    assert_eq!(lines[1 + 0x20], "32,0x20,' ',,x");
+    assert_eq!(lines[1 + 0x21], "33,0x21,'!',x,");
+
+    let (code, _, err) = lut(&["show", path.to_str().unwrap(), "Missing"]);
+    assert_eq!(code, 1);
+    assert!(err.ends_with("no table `Missing`, expected one of: Chars, Removed\n"), "{}", err);
+}
+
+#[test]
+fn diff() {
+    let old = description("diff", "old.toml", OLD);
+    let new = description("diff", "new.toml", NEW);
+    let (code, out, _) = lut(&["diff", old.to_str().unwrap(), new.to_str().unwrap()]);
+    assert_eq!(code, 1);
+    assert_eq!(out, "\
+Chars: cell type changed from u8 to u16
+Chars: flag `Token` is now set at 0x30..=0x39 ('0'..='9')
+Chars: flag `Token` is no longer set at 0x7B ('{')
+Chars: flag `Ws` was removed
+Chars: flag `Digit` was added
+table `Removed` was removed
+");
+
+    let (code, out, _) = lut(&["diff", old.to_str().unwrap(), old.to_str().unwrap()]);
+    assert_eq!((code, &*out), (0, ""));
+}
+
+#[test]
+fn check() {
+    let valid = description("check", "valid.toml", NEW);
+    let invalid = description("check", "invalid.json", r#"{
+        "table": [{ "name": "Chars", "cell_type": "u8", "size": 2,
+                    "flag": [{ "name": "A", "short_name": "A", "indices": [2] }] }]
+    }"#);
+    let (code, out, err) = lut(&["check", valid.to_str().unwrap(), invalid.to_str().unwrap()]);
+    assert_eq!(code, 1);
+    assert!(out.ends_with("valid.toml: table `Chars` uses 2 of 16 flags (u16)\n"), "{}", out);
+    assert!(err.ends_with("invalid.json: table `Chars` > flag `A` > indices[0]: \
+        2 is outside of the table with a size of 2\n"), "{}", err);
+}
+
+/// a description with one table of `count` flags, each set at its own index
+fn many_flags(count: usize) -> String {
+    let flags = (0..count)
+        .map(|idx| format!("{{ name = \"F{0}\", short_name = \"F{0}\", indices = [{0}] }}", idx))
+        .collect::<Vec<_>>();
+    format!("[[table]]\nname = \"Many\"\ncell_type = \"BitSet<{}>\"\nsize = {}\nflag = [\n{}\n]\n",
+        count.div_ceil(64) + 1, count, flags.join(",\n"))
+}
+
+#[test]
+fn show_picks_cell_width_from_flag_count() {
+    for &count in &[256, 257] {
+        let path = description("show-width", &format!("{}.toml", count), &many_flags(count));
+        let (code, out, err) = lut(&["check", path.to_str().unwrap()]);
+        assert_eq!(code, 0, "{}", err);
+        assert!(out.contains(&format!("uses {} of", count)), "{}", out);
+        let (code, out, err) = lut(&["show", path.to_str().unwrap(), "--format", "csv"]);
+        assert_eq!(code, 0, "{}", err);
+        let last = count - 1;
+        assert!(out.contains(&format!("\n{},0x{:02X},", last, last)), "{}", out);
+        assert!(out.ends_with(&format!("F{}\n", last)), "{}", out);
+    }
+
+    let path = description("show-width", "4097.toml", &many_flags(4097));
+    let (code, _, err) = lut(&["check", path.to_str().unwrap()]);
+    assert_eq!(code, 1);
+    assert!(err.ends_with("table `Many` has 4097 flags, but `show` renders at most 4096\n"), "{}", err);
+    let (code, _, err) = lut(&["show", path.to_str().unwrap()]);
+    assert_eq!(code, 1);
+    assert!(err.ends_with("table `Many` has 4097 flags, but `show` renders at most 4096\n"), "{}", err);
+}
+
+#[test]
+fn usage() {
+    let (code, _, err) = lut(&["frobnicate"]);
+    assert_eq!(code, 2);
+    assert!(err.starts_with("error: unknown command `frobnicate`\n\nusage: lut"), "{}", err);
+}
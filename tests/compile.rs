@@ -1,7 +1,5 @@
 #[macro_use]
 extern crate lut;
-#[macro_use]
-extern crate lazy_static;
 
 #[test]
 fn compile_only_tests() {